@@ -1,261 +1,967 @@
-use rust_chess::game::board::*;
-use rust_chess::game::rules::*;
+use crate::game::board::*;
+use crate::game::movegen::*;
+use crate::game::packed_move::*;
+use crate::game::rules::*;
+use crate::game::status::*;
+use crate::limits::*;
+use crate::ordering::*;
+use crate::pst::*;
+use crate::transposition::*;
 
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
 struct EvaluationCandidate {
-    mv: ChessMove,
-    value: f32,
+    mv: PackedMove,
+    value: i32,
 }
 
-impl PartialEq for EvaluationCandidate {
-    fn eq(&self, other: &Self) -> bool {
-        self.mv == other.mv
+impl EvaluationCandidate {
+    #[allow(clippy::redundant_field_names)]
+    fn new(mv: PackedMove, val: i32) -> Self {
+        EvaluationCandidate { mv: mv, value: val }
     }
 }
-impl Eq for EvaluationCandidate {}
-impl Ord for EvaluationCandidate {
-    fn cmp(&self, other: &Self) -> Ordering {
-        return self.value.partial_cmp(&other.value).unwrap();
-    }
+
+// longest line the search can go through before quiescence
+pub const MAX_PLY: usize = MAX_SEARCH_DEPTH + 1;
+
+// score of being mated right now, mate n plies from the root is scored MATE_SCORE - n
+pub const MATE_SCORE: i32 = 1000000;
+// scores closer to MATE_SCORE than this are mates, quiescence can go deeper than MAX_PLY
+const MAX_MATE_PLY: i32 = 1000;
+// greater than any score
+const INFINITE_SCORE: i32 = 10000000;
+
+// null move is tried only with this depth left, it is searched with depth reduced
+// by NULL_MOVE_REDUCTION (one more in deep searches) besides the move itself
+const NULL_MOVE_MIN_DEPTH: usize = 3;
+const NULL_MOVE_REDUCTION: usize = 2;
+// first window around the previous iteration score, doubled on every failure
+const ASPIRATION_WINDOW: i32 = 50;
+const ASPIRATION_MIN_DEPTH: usize = 4;
+// late move reductions apply to quiet moves after the first LMR_FULL_DEPTH_MOVES
+const LMR_FULL_DEPTH_MOVES: usize = 3;
+const LMR_MIN_DEPTH: usize = 3;
+// quiet moves with history above this are reduced less
+const LMR_GOOD_HISTORY: i32 = 1000;
+
+// score in the form people read it
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Score {
+    Centipawns(i32),
+    // in moves, negative if side to move gets mated
+    Mate(i32),
 }
-impl PartialOrd for EvaluationCandidate {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        return self.value.partial_cmp(&other.value);
-    }
+
+// result of evaluate, or of one finished iteration of search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    // first move of pv, None if there are no legal moves
+    pub best_move: Option<ChessMove>,
+    // in centipawns, from the point of view of side to move
+    pub score: i32,
+    // legal line from the root the score comes from
+    pub pv: Vec<PackedMove>,
+    // in search since its start, not only in this iteration
+    pub nodes: u64,
+    pub depth: usize,
+    pub time: Duration,
+    // best root moves with their own scores and lines, best first, as many as multipv
+    // setting asks for and there are legal moves; the first one is the same as above
+    pub lines: Vec<RootLine>,
 }
-impl EvaluationCandidate {
-    fn new(mv: ChessMove, val: f32) -> Self {
-        EvaluationCandidate { mv: mv, value: val }
-    }
+
+// one of the best moves at the root
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootLine {
+    pub mv: ChessMove,
+    // in centipawns, from the point of view of side to move
+    pub score: i32,
+    // legal line starting with mv
+    pub pv: Vec<PackedMove>,
 }
 
-struct PieceEvaluation {
-    pub king: f32,
-    pub queen: f32,
-    pub rook: f32,
-    pub knight: f32,
-    pub bishop: f32,
-    pub pawn: f32,
+// static evaluation split into middlegame and endgame parts, so moves can update
+// it incrementally and the phase decides how much each part counts
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct EvalTerms {
+    // in centipawns, from the point of view of white
+    pub mg: i32,
+    pub eg: i32,
+    // sum of PHASE_WEIGHTS of pieces on the board, MAX_PHASE at the start of the game
+    pub phase: i32,
 }
+
 pub struct Evaluator {
-    pieces_values: PieceEvaluation,
-    castle_value: f32,
-    pawn_pos_value: [f32; 8],
-    center_pos_value: [f32; 8],
+    // capture that can not bring eval closer than this to the window is skipped in quiescence
+    delta_margin: i32,
+    qsearch_check_evasions: bool,
+    // selectivity, each can be turned off to compare strength with and without it
+    null_move_pruning: bool,
+    late_move_reductions: bool,
+    check_extensions: bool,
 
-    pub low_level_eval_called: i32,
+    pub low_level_eval_called: u64,
+    // transposition table usage during last evaluate call
+    pub tt_probes: i32,
+    pub tt_hits: i32,
+    // beta cutoffs during last evaluate call, and how many of them were by the first move
+    pub beta_cutoffs: i32,
+    pub first_move_cutoffs: i32,
+
+    // search interruption, set from outside (e.g. UCI "stop") or by deadline
+    stop_flag: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    node_limit: Option<u64>,
+    aborted: bool,
+
+    // keys of positions played before the searched one, followed by current search path
+    history: Vec<u64>,
+    // moves from the root to the current node
+    path: Vec<PackedMove>,
+    // number of best root moves to find, the ones already found are not searched again
+    multipv: usize,
+    excluded_root_moves: Vec<PackedMove>,
+    ordering: MoveOrdering,
+
+    // triangular table, line from ply i is stored in pv_table[i][i..pv_len[i]]
+    pv_table: Vec<[PackedMove; MAX_PLY]>,
+    pv_len: Vec<usize>,
+
+    tt: TranspositionTable,
 }
 
-impl PieceEvaluation {
-    pub fn new() -> Self {
-        PieceEvaluation {
-            king: 1000.0,
-            queen: 9.0,
-            rook: 5.0,
-            knight: 3.0,
-            bishop: 3.0,
-            pawn: 1.0,
+impl Score {
+    // score is from the point of view of side to move
+    pub fn from_eval(score: i32) -> Self {
+        if !Self::is_mate(score) {
+            return Score::Centipawns(score);
         }
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        Score::Mate(if score > 0 { moves } else { -moves })
+    }
+
+    pub fn is_mate(score: i32) -> bool {
+        score.abs() >= MATE_SCORE - MAX_MATE_PLY
+    }
+}
+
+impl SearchResult {
+    pub fn get_score(&self) -> Score {
+        Score::from_eval(self.score)
+    }
+}
+
+impl RootLine {
+    pub fn get_score(&self) -> Score {
+        Score::from_eval(self.score)
+    }
+}
+
+impl EvalTerms {
+    pub fn add_piece(&mut self, piece: ChessPiece, idx: usize) {
+        let (mg, eg) = get_pst_value(piece, idx);
+        self.mg += mg;
+        self.eg += eg;
+        self.phase += get_phase_weight(piece);
+    }
+
+    pub fn remove_piece(&mut self, piece: ChessPiece, idx: usize) {
+        let (mg, eg) = get_pst_value(piece, idx);
+        self.mg -= mg;
+        self.eg -= eg;
+        self.phase -= get_phase_weight(piece);
+    }
+
+    // centipawns from the point of view of white, promotions can push phase over the maximum
+    pub fn get_value(&self) -> i32 {
+        let phase = self.phase.min(MAX_PHASE);
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
     }
 }
 
 impl Evaluator {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Evaluator {
-            pieces_values: PieceEvaluation::new(),
-            castle_value: 0.3,
-            pawn_pos_value: [0.0, 0.0, 0.05, 0.1, 0.1, 0.3, 1.0, 0.0],
-            center_pos_value: [0.0, 0.02, 0.1, 0.2, 0.2, 0.1, 0.02, 0.0],
+            delta_margin: 200,
+            qsearch_check_evasions: true,
+            null_move_pruning: true,
+            late_move_reductions: true,
+            check_extensions: true,
             low_level_eval_called: 0,
+            tt_probes: 0,
+            tt_hits: 0,
+            beta_cutoffs: 0,
+            first_move_cutoffs: 0,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+            node_limit: None,
+            aborted: false,
+            history: vec![],
+            path: vec![],
+            multipv: 1,
+            excluded_root_moves: vec![],
+            ordering: MoveOrdering::new(),
+            pv_table: vec![[PackedMove::NULL; MAX_PLY]; MAX_PLY],
+            pv_len: vec![0; MAX_PLY],
+            tt: TranspositionTable::new(DEFAULT_HASH_SIZE_MB),
         }
     }
 
-    pub fn get_piece_value(&self, piece: ChessPiece) -> f32 {
-        return match piece {
-            ChessPiece::None => 0.0,
-            ChessPiece::PawnBlack => -self.pieces_values.pawn,
-            ChessPiece::PawnWhite => self.pieces_values.pawn,
-            ChessPiece::RookBlack => -self.pieces_values.rook,
-            ChessPiece::RookWhite => self.pieces_values.rook,
-            ChessPiece::KnightBlack => -self.pieces_values.knight,
-            ChessPiece::KnightWhite => self.pieces_values.knight,
-            ChessPiece::BishopBlack => -self.pieces_values.bishop,
-            ChessPiece::BishopWhite => self.pieces_values.bishop,
-            ChessPiece::QueenBlack => -self.pieces_values.queen,
-            ChessPiece::QueenWhite => self.pieces_values.queen,
-            ChessPiece::KingBlack => -self.pieces_values.king,
-            ChessPiece::KingWhite => self.pieces_values.king,
-        };
+    // Search control
+    pub fn get_stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop_flag.clone()
+    }
+
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    // positions of the game before the one passed to evaluate, oldest first,
+    // search scores a repetition of any of them as a draw
+    pub fn set_history(&mut self, positions: &[ChessBoardState]) {
+        self.history = positions.iter().map(|x| x.hash).collect();
+    }
+
+    // search all moves in check during quiescence instead of captures only
+    pub fn set_qsearch_check_evasions(&mut self, enabled: bool) {
+        self.qsearch_check_evasions = enabled;
+    }
+
+    // number of best moves evaluate and search find with their lines, at least 1
+    pub fn set_multipv(&mut self, multipv: usize) {
+        self.multipv = multipv.max(1);
+    }
+
+    // skip a move and prune if the position is still too good for the opponent
+    pub fn set_null_move_pruning(&mut self, enabled: bool) {
+        self.null_move_pruning = enabled;
+    }
+
+    // search late quiet moves with less depth, again with full depth if they turn out good
+    pub fn set_late_move_reductions(&mut self, enabled: bool) {
+        self.late_move_reductions = enabled;
+    }
+
+    // search one ply deeper after moves that give check
+    pub fn set_check_extensions(&mut self, enabled: bool) {
+        self.check_extensions = enabled;
+    }
+
+    pub fn set_hash_size(&mut self, size_mb: usize) {
+        self.tt.resize(size_mb);
+    }
+
+    pub fn clear_hash(&mut self) {
+        self.tt.clear();
     }
 
-    pub fn evaluate(
+    // forget everything learned in previous searches
+    pub fn new_game(&mut self) {
+        self.tt.clear();
+        self.ordering.clear();
+    }
+
+    // permille of transposition table in use
+    pub fn get_hashfull(&self) -> usize {
+        self.tt.get_hashfull()
+    }
+
+    // share of probes that found the position, 0 if there were no probes
+    pub fn get_tt_hit_rate(&self) -> f32 {
+        if self.tt_probes == 0 {
+            return 0.0;
+        }
+        self.tt_hits as f32 / self.tt_probes as f32
+    }
+
+    // share of beta cutoffs made by the first searched move, 0 if there were no cutoffs
+    pub fn get_first_move_cutoff_rate(&self) -> f32 {
+        if self.beta_cutoffs == 0 {
+            return 0.0;
+        }
+        self.first_move_cutoffs as f32 / self.beta_cutoffs as f32
+    }
+
+    // true if last evaluate call was interrupted and its result must not be used
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    // board is repeated if same side was to move in the same position since
+    // the last capture or pawn move, history ends with the parent position
+    fn is_repetition(&self, board: &ChessBoardState) -> bool {
+        let len = self.history.len();
+        let reversible = (board.halfmoves_to_draw as usize).min(len);
+        (2..=reversible)
+            .step_by(2)
+            .any(|i| self.history[len - i] == board.hash)
+    }
+
+    fn check_abort(&mut self) -> bool {
+        if !self.aborted && self.low_level_eval_called.is_multiple_of(1024) {
+            self.aborted = self.stop_flag.load(AtomicOrdering::Relaxed)
+                || self.deadline.is_some_and(|d| Instant::now() >= d)
+                || self
+                    .node_limit
+                    .is_some_and(|n| self.low_level_eval_called >= n);
+        }
+        self.aborted
+    }
+
+    // static evaluation in centipawns from the point of view of white
+    pub fn get_static_eval(&self, board: &ChessBoardState) -> i32 {
+        self.get_eval_terms(board).get_value()
+    }
+
+    pub fn get_eval_terms(&self, board: &ChessBoardState) -> EvalTerms {
+        let mut res = EvalTerms::default();
+        for (i, piece) in board.board.iter().enumerate() {
+            if *piece != ChessPiece::None {
+                res.add_piece(*piece, i);
+            }
+        }
+        res
+    }
+
+    pub fn evaluate(&mut self, board: &ChessBoardState, depth: usize) -> SearchResult {
+        self.evaluate_around(board, depth, &[])
+    }
+
+    // evaluate with aspiration windows around expected scores of the lines (from the point
+    // of view of side to move), usually the ones from the previous iteration
+    fn evaluate_around(
         &mut self,
         board: &ChessBoardState,
         depth: usize,
-    ) -> (f32, Vec<(ChessMove, f32)>) {
+        guesses: &[i32],
+    ) -> SearchResult {
+        let start = Instant::now();
+        let depth = depth.clamp(1, MAX_SEARCH_DEPTH);
         self.low_level_eval_called = 0;
-        let cur_eval = self.simple_eval(board);
+        self.tt_probes = 0;
+        self.tt_hits = 0;
+        self.beta_cutoffs = 0;
+        self.first_move_cutoffs = 0;
+        self.aborted = false;
+        self.excluded_root_moves.clear();
+        let mut lines: Vec<RootLine> = vec![];
+        let mut score = None;
+        // every next line is the best one among moves not in previous lines
+        for i in 0..self.multipv {
+            let value = self.search_root(board, depth, guesses.get(i).copied());
+            score = score.or(Some(value));
+            if self.aborted {
+                break;
+            }
+            let pv = self.get_pv(board);
+            let Some(&mv) = pv.first() else {
+                break;
+            };
+            lines.push(RootLine {
+                mv: mv.unpack(),
+                score: value,
+                pv,
+            });
+            self.excluded_root_moves.push(mv);
+        }
+        self.excluded_root_moves.clear();
+        let (best_move, pv) = match lines.first() {
+            Some(x) => (Some(x.mv), x.pv.clone()),
+            None => (None, vec![]),
+        };
+        SearchResult {
+            best_move,
+            score: score.unwrap_or(0),
+            pv,
+            nodes: self.low_level_eval_called,
+            depth,
+            time: start.elapsed(),
+            lines,
+        }
+    }
+
+    // score of the root from the point of view of side to move, window around the guess
+    // is widened and the search repeated while the score is outside of it
+    fn search_root(&mut self, board: &ChessBoardState, depth: usize, guess: Option<i32>) -> i32 {
+        let terms = self.get_eval_terms(board);
         let max = board.turn == Color::White;
-        let mut branch = vec![Self::get_base_move(0.0); depth];
-        return (
-            self.eval(
-                cur_eval,
-                -1000000.0,
-                1000000.0,
-                *board,
-                max,
-                depth,
-                &mut branch,
-            ),
-            branch.iter().map(|x| (x.mv, x.value)).collect(), //TODO refactor
-        );
-    }
-
-    fn get_base_move(value: f32) -> EvaluationCandidate {
-        EvaluationCandidate {
-            mv: ChessMove {
-                mv: Move::from_str("a1-a1"),
-                move_type: ChessMoveType::Simple,
-            },
-            value,
+        // the only copy, search makes and unmakes moves on it
+        let mut cur = *board;
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match guess {
+            Some(x) if depth >= ASPIRATION_MIN_DEPTH && !Score::is_mate(x) => {
+                (x - delta, x + delta)
+            }
+            _ => (-MATE_SCORE, MATE_SCORE),
+        };
+        loop {
+            let value = if max {
+                self.eval(terms, alpha, beta, &mut cur, max, depth, 0)
+            } else {
+                -self.eval(terms, -beta, -alpha, &mut cur, max, depth, 0)
+            };
+            if self.aborted {
+                break value;
+            }
+            if value <= alpha && alpha > -MATE_SCORE {
+                alpha = (alpha - delta).max(-MATE_SCORE);
+            } else if value >= beta && beta < MATE_SCORE {
+                beta = (beta + delta).min(MATE_SCORE);
+            } else {
+                break value;
+            }
+            delta *= 2;
         }
     }
 
-    // TODO refactor config in Evaluator 
-    #[warn(dead_code)]
-    fn get_depth(_: usize, cur: usize, depth: usize) -> usize {
-        if depth >= 1 && cur < 6 {
-            return depth - 1;
+    // iterative deepening until one of the limits is reached, calls report after every
+    // finished iteration and returns best move of the last one (None if there are no legal moves)
+    pub fn search<F: FnMut(&SearchResult)>(
+        &mut self,
+        board: &ChessBoardState,
+        limits: &SearchLimits,
+        mut report: F,
+    ) -> Option<ChessMove> {
+        let time_manager = TimeManager::new(limits, board.turn);
+        let max_nodes = limits.get_max_nodes();
+        let legal_moves = board.get_legal_moves();
+        let mut best_move = legal_moves.first().map(|x| x.unpack());
+        if legal_moves.len() <= 1 {
+            return best_move;
         }
-        if depth >= 3 {
-            return depth - 3;
+        let mut nodes = 0;
+        let mut scores = vec![];
+        for depth in 1..=limits.get_max_depth() {
+            // first iteration is always finished so we have a move to play
+            if depth > 1 {
+                self.set_deadline(time_manager.get_deadline());
+                self.node_limit = max_nodes.map(|x| x.saturating_sub(nodes));
+            }
+            let mut res = self.evaluate_around(board, depth, &scores);
+            nodes += res.nodes;
+            if self.aborted {
+                break;
+            }
+            scores = res.lines.iter().map(|x| x.score).collect();
+            if res.best_move.is_some() {
+                best_move = res.best_move;
+            }
+            res.nodes = nodes;
+            res.time = time_manager.get_elapsed();
+            report(&res);
+            if time_manager.should_stop() || max_nodes.is_some_and(|x| nodes >= x) {
+                break;
+            }
         }
-        return (depth + 1) % 2;
+        self.deadline = None;
+        self.node_limit = None;
+        best_move
     }
 
+    // root line of the table, cut at the first move that is not legal in its position
+    fn get_pv(&self, board: &ChessBoardState) -> Vec<PackedMove> {
+        let mut res = vec![];
+        let mut cur = *board;
+        for mv in &self.pv_table[0][..self.pv_len[0]] {
+            if !cur.get_legal_moves().contains(mv) {
+                break;
+            }
+            res.push(*mv);
+            cur.make_move(mv.unpack());
+        }
+        res
+    }
+
+    // best move at ply followed by the line of its child
+    fn update_pv(&mut self, ply: usize, mv: PackedMove) {
+        let child_len = self.pv_len[ply + 1];
+        let (parent, child) = self.pv_table.split_at_mut(ply + 1);
+        parent[ply][ply] = mv;
+        parent[ply][ply + 1..child_len].copy_from_slice(&child[0][ply + 1..child_len]);
+        self.pv_len[ply] = child_len;
+    }
+
+    // moves with equal ordering scores go by squares as seen by side to move,
+    // so mirrored positions are searched in the same order
+    fn get_tie_break(board: &ChessBoardState, mv: PackedMove) -> i32 {
+        let mv = if board.turn == Color::White {
+            mv
+        } else {
+            mv.get_mirrored()
+        };
+        -(mv.get_code() as i32)
+    }
+
+    fn get_base_move(value: i32) -> EvaluationCandidate {
+        EvaluationCandidate {
+            mv: PackedMove::NULL,
+            value,
+        }
+    }
+
+    #[allow(clippy::needless_return, clippy::too_many_arguments)]
     fn eval(
         &mut self,
-        cur_eval: f32,
-        mut alpha: f32,
-        mut beta: f32,
-        board: ChessBoardState,
+        terms: EvalTerms,
+        mut alpha: i32,
+        mut beta: i32,
+        board: &mut ChessBoardState,
         max: bool,
         depth: usize,
-        branch: &mut Vec<EvaluationCandidate>,
-    ) -> f32 {
-        if depth == 0 {
+        ply: usize,
+    ) -> i32 {
+        self.pv_len[ply] = ply;
+        // extensions can make the line longer than the tables,
+        // node at the horizon is counted by qsearch
+        let horizon = depth == 0 || ply + 1 >= MAX_PLY;
+        if !horizon {
             self.low_level_eval_called += 1;
-            return cur_eval;
+            if self.check_abort() {
+                return terms.get_value();
+            }
         }
-        let all_moves = board.get_all_moves();
-        if all_moves.is_empty() {
-            return if !max { 1000000.0 } else { -1000000.0 };
+        let root = ply == 0;
+        if !root && self.is_repetition(board) {
+            return 0;
         }
-        let mut moves_queue = BinaryHeap::<EvaluationCandidate>::new();
-        for mv in all_moves {
-            let (new_board, res) = board.get_new_pos_after_move_for_eval(mv); // TODO optimise even more dont make new board twice
-            let value = if max {
-                cur_eval + self.get_result_eval_diff(&new_board, res, mv)
+        if !root {
+            // mate distance pruning, line can not be better than mate on the next move
+            // or worse than mate right now, so a shorter mate found earlier ends the search
+            let mate = MATE_SCORE - ply as i32;
+            let (lower, upper) = if max {
+                (-mate, mate - 1)
             } else {
-                -cur_eval - self.get_result_eval_diff(&new_board, res, mv)
+                (-mate + 1, mate)
             };
-            moves_queue.push(EvaluationCandidate {
-                mv: mv,
-                value: value,
-            });
+            alpha = alpha.max(lower);
+            beta = beta.min(upper);
+            if alpha >= beta {
+                return if max { alpha } else { beta };
+            }
+        }
+        if horizon {
+            return self.qsearch(terms, alpha, beta, board, max, ply);
+        }
+        let mut moves = board.get_legal_moves();
+        match board.get_game_status_with_moves(&moves) {
+            GameStatus::Ongoing => {}
+            GameStatus::Checkmate(_) => return Self::get_mated_score(max, ply),
+            GameStatus::Stalemate => return 0,
+            // draws by rule are only used below root, there we still need a move
+            _ if !root => return 0,
+            _ => {}
+        }
+        // lines already found in multipv search
+        let excluding = root && !self.excluded_root_moves.is_empty();
+        if excluding {
+            let mut rest = MoveList::new();
+            for mv in moves.iter() {
+                if !self.excluded_root_moves.contains(mv) {
+                    rest.push(*mv);
+                }
+            }
+            moves = rest;
+        }
+
+        // node on the principal variation has a full window, others only prove a bound
+        let pv_node = beta - alpha > 1;
+        self.tt_probes += 1;
+        let tt_entry = self.tt.probe(board.hash);
+        if let Some(x) = tt_entry {
+            self.tt_hits += 1;
+            // pv nodes, root among them, are searched to have the whole line in pv
+            let score = Self::get_score_from_tt(x.score, ply);
+            if !pv_node && x.depth as usize >= depth {
+                match x.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+        let hash_move = tt_entry.and_then(|x| x.best_move);
+        let (alpha_orig, beta_orig) = (alpha, beta);
+        let in_check = board.get_king_attacked(board.turn);
+
+        if !pv_node && !in_check && self.can_try_null_move(terms, board, alpha, beta, max, depth) {
+            let reduction = NULL_MOVE_REDUCTION + if depth > 6 { 1 } else { 0 };
+            let undo = board.make_null_move();
+            self.history.push(undo.hash);
+            self.path.push(PackedMove::NULL);
+            // zero window at the bound the side to move wants to reach
+            let (null_alpha, null_beta) = if max {
+                (beta - 1, beta)
+            } else {
+                (alpha, alpha + 1)
+            };
+            let value = self.eval(
+                terms,
+                null_alpha,
+                null_beta,
+                board,
+                !max,
+                depth.saturating_sub(1 + reduction),
+                ply + 1,
+            );
+            self.path.pop();
+            self.history.pop();
+            board.unmake_null_move(&undo);
+            if self.aborted {
+                return terms.get_value();
+            }
+            // even without moving the opponent can not get back into the window,
+            // mates found without a move are not real, so only the bound is returned
+            if max && value >= beta {
+                return if Score::is_mate(value) { beta } else { value };
+            }
+            if !max && value <= alpha {
+                return if Score::is_mate(value) { alpha } else { value };
+            }
         }
-        let mut best_eval = Self::get_base_move(if !max { 10000000.0 } else { -10000000.0 });
-        let moves_num = moves_queue.len();
-        for i in 0..moves_num {
-            let mv = moves_queue.pop().unwrap();
+
+        let prev = self.path.last().copied().unwrap_or(PackedMove::NULL);
+        let mut scores = self
+            .ordering
+            .score_moves(board, &moves, hash_move, ply, prev);
+        let mut best_eval = Self::get_base_move(if !max {
+            INFINITE_SCORE
+        } else {
+            -INFINITE_SCORE
+        });
+        self.history.push(board.hash);
+        for i in 0..moves.len() {
+            // selection sort step, moves after a cutoff are never sorted
+            let next = (i..moves.len())
+                .max_by_key(|x| (scores[*x], Self::get_tie_break(board, moves[*x])))
+                .unwrap();
+            moves.swap(i, next);
+            scores.swap(i, next);
+            let mv = moves[i];
+            let quiet = !mv.is_capture() && !mv.is_promotion();
+            let history = self.ordering.get_history(board.turn, mv);
+            let killer = self.ordering.get_killers(ply).contains(&mv);
             let eval = {
-                let (new_board, res) = board.get_new_pos_after_move_for_eval(mv.mv);
-                if res.remove == ChessPiece::KingBlack || res.remove == ChessPiece::KingWhite {
-                    Self::get_base_move(-Self::get_piece_value(&self, res.remove))
+                let undo = board.make_move(mv.unpack());
+                let new_terms = Self::get_terms_after_move(terms, board, &undo);
+                let gives_check = board.get_king_attacked(board.turn);
+                let mut new_depth = depth - 1;
+                if self.check_extensions && gives_check {
+                    new_depth += 1;
+                }
+                let reduction = if self.late_move_reductions
+                    && quiet
+                    && !killer
+                    && !in_check
+                    && !gives_check
+                    && i >= LMR_FULL_DEPTH_MOVES
+                    && depth >= LMR_MIN_DEPTH
+                {
+                    Self::get_lmr_reduction(i, depth, history).min(new_depth - 1)
+                } else {
+                    0
+                };
+                self.path.push(mv);
+                let value = if i == 0 {
+                    self.eval(new_terms, alpha, beta, board, !max, new_depth, ply + 1)
                 } else {
-                    let new_depth = Self::get_depth(moves_queue.len(), i, depth);
-                    let value = self.eval(
-                        cur_eval + self.get_result_eval_diff(&new_board, res, mv.mv),
-                        alpha,
-                        beta,
-                        new_board,
+                    // principal variation search: later moves only have to be proven worse
+                    // than the best one, zero window next to the bound is enough for that
+                    let (zw_alpha, zw_beta) = if max {
+                        (alpha, alpha + 1)
+                    } else {
+                        (beta - 1, beta)
+                    };
+                    let mut value = self.eval(
+                        new_terms,
+                        zw_alpha,
+                        zw_beta,
+                        board,
                         !max,
-                        new_depth,
-                        branch,
+                        new_depth - reduction,
+                        ply + 1,
                     );
-                    EvaluationCandidate::new(mv.mv, value)
-                }
+                    // reduced move turned out better than expected, it needs a full depth search
+                    if reduction > 0 && (max && value > alpha || !max && value < beta) {
+                        value = self.eval(
+                            new_terms,
+                            zw_alpha,
+                            zw_beta,
+                            board,
+                            !max,
+                            new_depth,
+                            ply + 1,
+                        );
+                    }
+                    // better than the best move so far, exact score is needed
+                    if value > alpha && value < beta {
+                        value = self.eval(new_terms, alpha, beta, board, !max, new_depth, ply + 1);
+                    }
+                    value
+                };
+                self.path.pop();
+                board.unmake_move(&undo);
+                EvaluationCandidate::new(mv, value)
             };
 
+            if self.aborted {
+                break;
+            }
             if max && eval.value > best_eval.value || !max && eval.value < best_eval.value {
-                best_eval = eval.clone();
+                best_eval = eval;
+                self.update_pv(ply, eval.mv);
             }
-            if max {
-                if eval.value > beta {
-                    break;
+            if max && eval.value >= beta || !max && eval.value <= alpha {
+                self.beta_cutoffs += 1;
+                if i == 0 {
+                    self.first_move_cutoffs += 1;
                 }
-                if eval.value > alpha {
-                    alpha = eval.value
+                if !mv.is_capture() && !mv.is_promotion() {
+                    self.ordering.update_cutoff(board, mv, ply, depth, prev);
+                }
+                break;
+            }
+            if max {
+                alpha = alpha.max(eval.value);
+            } else {
+                beta = beta.min(eval.value);
+            }
+        }
+        self.history.pop();
+
+        // score without some of the moves is not the score of the position
+        if !self.aborted && !excluding {
+            // value outside of the original window is only a bound
+            let bound = if best_eval.value >= beta_orig {
+                Bound::Lower
+            } else if best_eval.value <= alpha_orig {
+                Bound::Upper
+            } else {
+                Bound::Exact
+            };
+            self.tt.store(TTEntry {
+                key: board.hash,
+                depth: depth as u8,
+                score: Self::get_score_to_tt(best_eval.value, ply),
+                bound,
+                best_move: Some(best_eval.mv),
+            });
+        }
+
+        return best_eval.value;
+    }
+
+    // null move needs a position already good enough without moving, and pieces besides pawns,
+    // with only king and pawns being forced to move (zugzwang) is common
+    fn can_try_null_move(
+        &self,
+        terms: EvalTerms,
+        board: &ChessBoardState,
+        alpha: i32,
+        beta: i32,
+        max: bool,
+        depth: usize,
+    ) -> bool {
+        if !self.null_move_pruning || depth < NULL_MOVE_MIN_DEPTH {
+            return false;
+        }
+        // two null moves in a row would only search the same position with less depth
+        if self.path.last() == Some(&PackedMove::NULL) {
+            return false;
+        }
+        let pawns_and_kings = board.get_piece_bb(ChessPiece::PawnWhite)
+            | board.get_piece_bb(ChessPiece::PawnBlack)
+            | board.get_piece_bb(ChessPiece::KingWhite)
+            | board.get_piece_bb(ChessPiece::KingBlack);
+        if board.get_color_bb(board.turn) & !pawns_and_kings == 0 {
+            return false;
+        }
+        let value = terms.get_value();
+        if max {
+            value >= beta && !Score::is_mate(beta)
+        } else {
+            value <= alpha && !Score::is_mate(alpha)
+        }
+    }
+
+    // more for later moves and deeper searches, less for moves that caused cutoffs before
+    fn get_lmr_reduction(idx: usize, depth: usize, history: i32) -> usize {
+        let mut res = 1;
+        if idx >= 2 * LMR_FULL_DEPTH_MOVES {
+            res += 1;
+        }
+        if depth >= 2 * LMR_MIN_DEPTH {
+            res += 1;
+        }
+        if history > LMR_GOOD_HISTORY {
+            res -= 1;
+        }
+        res
+    }
+
+    // captures and promotions only, so the position is not evaluated in the middle of exchange
+    fn qsearch(
+        &mut self,
+        terms: EvalTerms,
+        mut alpha: i32,
+        mut beta: i32,
+        board: &mut ChessBoardState,
+        max: bool,
+        ply: usize,
+    ) -> i32 {
+        self.low_level_eval_called += 1;
+        let cur_eval = terms.get_value();
+        if self.check_abort() {
+            return cur_eval;
+        }
+        let in_check = self.qsearch_check_evasions && board.get_king_attacked(board.turn);
+        let mut best = if max { -INFINITE_SCORE } else { INFINITE_SCORE };
+        if !in_check {
+            // stand pat, side to move is not forced to capture
+            best = cur_eval;
+            if max {
+                if cur_eval >= beta {
+                    return cur_eval;
                 }
+                alpha = alpha.max(cur_eval);
             } else {
-                if eval.value < alpha {
+                if cur_eval <= alpha {
+                    return cur_eval;
+                }
+                beta = beta.min(cur_eval);
+            }
+        }
+
+        let mut moves = vec![];
+        let candidates = if in_check {
+            board.get_legal_moves()
+        } else {
+            board.get_legal_captures()
+        };
+        for &mv in candidates.iter() {
+            // capture that loses material is not going to improve the stand pat score
+            if !in_check && board.see(mv.unpack()) < 0 {
+                continue;
+            }
+            let undo = board.make_move(mv.unpack());
+            let new_terms = Self::get_terms_after_move(terms, board, &undo);
+            board.unmake_move(&undo);
+            let value = new_terms.get_value();
+            // delta pruning, even a good continuation would not reach the window
+            if !in_check
+                && (max && value + self.delta_margin < alpha
+                    || !max && value - self.delta_margin > beta)
+            {
+                continue;
+            }
+            moves.push((mv, new_terms, value));
+        }
+        if in_check && moves.is_empty() {
+            return Self::get_mated_score(max, ply);
+        }
+        // most valuable captures first
+        if max {
+            moves.sort_by_key(|x| -x.2);
+        } else {
+            moves.sort_by_key(|x| x.2);
+        }
+
+        for (mv, new_terms, _) in moves {
+            let undo = board.make_move(mv.unpack());
+            let eval = self.qsearch(new_terms, alpha, beta, board, !max, ply + 1);
+            board.unmake_move(&undo);
+            if self.aborted {
+                break;
+            }
+            if max {
+                best = best.max(eval);
+                if eval >= beta {
                     break;
                 }
-                if eval.value < beta {
-                    beta = eval.value
+                alpha = alpha.max(eval);
+            } else {
+                best = best.min(eval);
+                if eval <= alpha {
+                    break;
                 }
+                beta = beta.min(eval);
             }
         }
+        best
+    }
 
-        branch[depth - 1] = best_eval;
-        return best_eval.value;
+    // side to move is mated, scores are from the point of view of white as everywhere here
+    fn get_mated_score(max: bool, ply: usize) -> i32 {
+        let score = MATE_SCORE - ply as i32;
+        if max { -score } else { score }
     }
 
-    fn get_result_eval_diff(
-        &self,
+    // table keeps distance to mate from the stored position, not from the root
+    fn get_score_to_tt(score: i32, ply: usize) -> i32 {
+        if !Score::is_mate(score) {
+            return score;
+        }
+        if score > 0 {
+            score + ply as i32
+        } else {
+            score - ply as i32
+        }
+    }
+
+    fn get_score_from_tt(score: i32, ply: usize) -> i32 {
+        if !Score::is_mate(score) {
+            return score;
+        }
+        if score > 0 {
+            score - ply as i32
+        } else {
+            score + ply as i32
+        }
+    }
+
+    // board is the position after the move
+    pub fn get_terms_after_move(
+        terms: EvalTerms,
         board: &ChessBoardState,
-        move_res: MoveResult,
-        mv: ChessMove,
-    ) -> f32 {
-        let piece = board.get_piece_unsafe(mv.mv.to);
-        let color = piece.get_color().unwrap();
-        let mut sum = 0.0;
-        let mult = if color == Color::White { 1.0 } else { -1.0 };
-        if mv.move_type == ChessMoveType::CastleLong || mv.move_type == ChessMoveType::CastleLong {
-            sum += self.castle_value * mult;
-        }
-        sum += self.get_piece_value_from_pos(piece, mv.mv.to)
-            - self.get_piece_value_from_pos(piece, mv.mv.from);
-        sum += self.get_piece_value(move_res.new) - self.get_piece_value(move_res.remove);
-        return sum;
-    }
-
-    fn get_piece_value_from_pos(&self, piece: ChessPiece, pos: Pos) -> f32 {
-        let color = piece.get_color().unwrap();
-        let mult = if color == Color::White { 1.0 } else { -1.0 };
-        if piece == ChessPiece::PawnWhite {
-            return self.pawn_pos_value[pos.y as usize] + (self.center_pos_value[pos.x as usize] + self.center_pos_value[pos.y as usize]) / 4.0;
-        }
-        if piece == ChessPiece::PawnBlack {
-            return -(self.center_pos_value[pos.x as usize] * self.center_pos_value[pos.y as usize]) * 10.0;
-            // return -self.pawn_pos_value[7 - pos.y as usize] - (self.center_pos_value[pos.x as usize] + self.center_pos_value[pos.y as usize]) * 10.0;
-        }
-        return mult
-            * (self.center_pos_value[pos.x as usize] + self.center_pos_value[pos.y as usize])
-            / 2.0;
-    }
-
-    fn simple_eval(&self, board: &ChessBoardState) -> f32 {
-        let mut eval = 0.0;
-        for x in 0..BOARD_SIZE as i8 {
-            for y in 0..BOARD_SIZE as i8 {
-                let piece = board.get_piece_unsafe(Pos::from_coords(x, y));
-                eval += self.get_piece_value(piece);
+        undo: &UndoRecord,
+    ) -> EvalTerms {
+        let mut res = terms;
+        let mv = undo.mv.mv;
+        let piece = board.get_piece_unsafe(mv.to);
+        let moved = match undo.mv.move_type {
+            ChessMoveType::Promotion(_) if board.turn == Color::Black => ChessPiece::PawnWhite,
+            ChessMoveType::Promotion(_) => ChessPiece::PawnBlack,
+            _ => piece,
+        };
+        res.remove_piece(moved, ChessBoardState::get_pos_idx(mv.from));
+        res.add_piece(piece, ChessBoardState::get_pos_idx(mv.to));
+        match undo.mv.move_type {
+            ChessMoveType::EnPassant => {
+                let captured = Pos::from_coords(mv.to.x as i8, mv.from.y as i8);
+                res.remove_piece(undo.result.remove, ChessBoardState::get_pos_idx(captured));
+            }
+            ChessMoveType::CastleShort | ChessMoveType::CastleLong => {
+                let (rook_from, rook_to) = if undo.mv.move_type == ChessMoveType::CastleShort {
+                    (7, 5)
+                } else {
+                    (0, 3)
+                };
+                let rook_from = Pos::from_coords(rook_from, mv.from.y as i8);
+                let rook_to = Pos::from_coords(rook_to, mv.from.y as i8);
+                let rook = board.get_piece_unsafe(rook_to);
+                res.remove_piece(rook, ChessBoardState::get_pos_idx(rook_from));
+                res.add_piece(rook, ChessBoardState::get_pos_idx(rook_to));
+            }
+            _ if undo.result.remove != ChessPiece::None => {
+                res.remove_piece(undo.result.remove, ChessBoardState::get_pos_idx(mv.to));
             }
+            _ => {}
         }
-        return eval;
+        res
     }
 }
@@ -0,0 +1,343 @@
+use super::board::*;
+
+use std::sync::OnceLock;
+
+// set of squares, bit i is square with board index i (a1 = 0, h1 = 7, a8 = 56)
+pub type Bitboard = u64;
+
+pub const FILE_A: Bitboard = 0x0101010101010101;
+pub const RANK_1: Bitboard = 0xFF;
+
+// Sliding attacks use fancy magic bitboards: relevant blockers of a square are
+// multiplied by the magic and shifted, which gives unique index into attack table.
+// Magics were found by random trial with shift equal to the number of relevant squares
+const ROOK_MAGICS: [u64; 64] = [
+    0x0980008011400020,
+    0x8340004410002000,
+    0x0880200090008268,
+    0x0080080080100004,
+    0x8100110004020800,
+    0x0300010004000822,
+    0x08801A0029000080,
+    0x8100050001204882,
+    0x0844800081400320,
+    0x0804402010004000,
+    0x0108802003100480,
+    0x0004808008001000,
+    0x0003001801001014,
+    0x0002000200041008,
+    0x0004008108042210,
+    0x0105000100009042,
+    0x0400808000400021,
+    0xC100404010002000,
+    0x0060008010002088,
+    0x0400808008001000,
+    0x4440808008000400,
+    0x1002008004000280,
+    0x40024400300D1248,
+    0x0010020000408104,
+    0x0101008200204200,
+    0x8020002040005000,
+    0x4100100080802000,
+    0x4008006A80100280,
+    0x1020080080040080,
+    0x0004010040020040,
+    0x0018A12400080290,
+    0x6140004200008104,
+    0x4000400020800090,
+    0x2020002080804000,
+    0x0000408202002010,
+    0x0080100501000820,
+    0x0000800400800800,
+    0x000A200408014010,
+    0x0100800200800100,
+    0xA00800570200008C,
+    0x008000406000C010,
+    0x1040100028002000,
+    0x0048200100110040,
+    0x0068490210030020,
+    0x1009080005010010,
+    0x2142000804010100,
+    0x1001080110840002,
+    0x1801004400820001,
+    0x010440208D020200,
+    0x0000400020008080,
+    0x0200200080100280,
+    0x0000100020090100,
+    0x0204008008020480,
+    0x8104010040020040,
+    0x78000201B0080400,
+    0x0040800051002880,
+    0x0050108001002041,
+    0x208A801100614003,
+    0x0006002042089082,
+    0x0011090004201001,
+    0x1002001004200802,
+    0x0005000208040001,
+    0x0002002701AC0822,
+    0x000010250184004A,
+];
+const BISHOP_MAGICS: [u64; 64] = [
+    0xC0A0012206040EA0,
+    0x8010228200420001,
+    0x0110008220400400,
+    0x02445C0080106000,
+    0x0044042004008100,
+    0x0880900420408C05,
+    0x0201080110080002,
+    0x0000108094202000,
+    0x0000042002040108,
+    0x0000623024110042,
+    0x0086100094811002,
+    0x0000044502002080,
+    0x0100460211400040,
+    0x0008109004200004,
+    0x0202320084844000,
+    0x8040042421041009,
+    0x201010C05102008C,
+    0x1020888208024080,
+    0x0108000C80290200,
+    0x8048000420425203,
+    0x0005000090402000,
+    0x2080400201104100,
+    0x8820420111101000,
+    0x4AC0302208821802,
+    0x000440001002A840,
+    0x2002200010041080,
+    0x1012080201004400,
+    0x8440040002410120,
+    0x1090820084010400,
+    0x2084852012021000,
+    0x12040062C1011003,
+    0x02008205E1090080,
+    0x088C102808042080,
+    0x0802102200904280,
+    0x8020209002080020,
+    0x2200080800060A00,
+    0x20C0004010010100,
+    0x0802004100821003,
+    0x0008024400008080,
+    0x0000840102008090,
+    0x0030A40420244007,
+    0x0A19084210011282,
+    0x0004082090019806,
+    0x6108004208020080,
+    0x0081200410110100,
+    0x1040810701010208,
+    0x0282047832012080,
+    0x0010020099000020,
+    0x000E010422400840,
+    0x10204208B0089090,
+    0x081004440C048000,
+    0x88C0180084040001,
+    0x3100020803040080,
+    0x890070A041210C00,
+    0x0020200101010A09,
+    0x0004100240410400,
+    0x0006004402080200,
+    0x0801062484042000,
+    0x00010002D7441004,
+    0x0810080000208800,
+    0x0000020808030411,
+    0x1450001020014440,
+    0x004060081081A288,
+    0x0044011404108A00,
+];
+
+const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (-1, -1), (1, -1), (-1, 1)];
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+#[derive(Clone, Copy, Default)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+struct AttackTables {
+    knight: [Bitboard; BOARD_ARRAY_SIZE],
+    king: [Bitboard; BOARD_ARRAY_SIZE],
+    // indexed by color of the pawn
+    pawn: [[Bitboard; BOARD_ARRAY_SIZE]; 2],
+    rook: [Magic; BOARD_ARRAY_SIZE],
+    bishop: [Magic; BOARD_ARRAY_SIZE],
+    sliding: Vec<Bitboard>,
+}
+
+static TABLES: OnceLock<AttackTables> = OnceLock::new();
+
+pub fn square_bb(pos: Pos) -> Bitboard {
+    1 << ChessBoardState::get_pos_idx(pos)
+}
+
+pub fn square_pos(idx: usize) -> Pos {
+    Pos {
+        x: (idx % BOARD_SIZE) as u8,
+        y: (idx / BOARD_SIZE) as u8,
+    }
+}
+
+// removes the lowest square from the set and returns its index
+pub fn pop_lsb(bb: &mut Bitboard) -> usize {
+    let idx = bb.trailing_zeros() as usize;
+    *bb &= *bb - 1;
+    idx
+}
+
+pub fn knight_attacks(idx: usize) -> Bitboard {
+    get_tables().knight[idx]
+}
+
+pub fn king_attacks(idx: usize) -> Bitboard {
+    get_tables().king[idx]
+}
+
+// squares attacked by a pawn of given color standing on idx
+pub fn pawn_attacks(color: Color, idx: usize) -> Bitboard {
+    get_tables().pawn[color as usize][idx]
+}
+
+pub fn rook_attacks(idx: usize, occupied: Bitboard) -> Bitboard {
+    let tables = get_tables();
+    tables.sliding[tables.rook[idx].get_index(occupied)]
+}
+
+pub fn bishop_attacks(idx: usize, occupied: Bitboard) -> Bitboard {
+    let tables = get_tables();
+    tables.sliding[tables.bishop[idx].get_index(occupied)]
+}
+
+pub fn queen_attacks(idx: usize, occupied: Bitboard) -> Bitboard {
+    rook_attacks(idx, occupied) | bishop_attacks(idx, occupied)
+}
+
+// squares strictly between two squares on one line, empty if they are not aligned
+pub fn between_bb(from: usize, to: usize) -> Bitboard {
+    let (from_bb, to_bb) = (1 << from, 1 << to);
+    if rook_attacks(from, 0) & to_bb != 0 {
+        return rook_attacks(from, to_bb) & rook_attacks(to, from_bb);
+    }
+    if bishop_attacks(from, 0) & to_bb != 0 {
+        return bishop_attacks(from, to_bb) & bishop_attacks(to, from_bb);
+    }
+    0
+}
+
+fn get_tables() -> &'static AttackTables {
+    TABLES.get_or_init(AttackTables::new)
+}
+
+impl Magic {
+    fn get_index(&self, occupied: Bitboard) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+impl AttackTables {
+    fn new() -> Self {
+        let mut res = AttackTables {
+            knight: [0; BOARD_ARRAY_SIZE],
+            king: [0; BOARD_ARRAY_SIZE],
+            pawn: [[0; BOARD_ARRAY_SIZE]; 2],
+            rook: [Magic::default(); BOARD_ARRAY_SIZE],
+            bishop: [Magic::default(); BOARD_ARRAY_SIZE],
+            sliding: vec![],
+        };
+        for idx in 0..BOARD_ARRAY_SIZE {
+            res.knight[idx] = Self::get_step_attacks(idx, &KNIGHT_STEPS);
+            res.king[idx] =
+                Self::get_step_attacks(idx, &ROOK_DIRS) | Self::get_step_attacks(idx, &BISHOP_DIRS);
+            res.pawn[Color::White as usize][idx] = Self::get_step_attacks(idx, &[(1, 1), (-1, 1)]);
+            res.pawn[Color::Black as usize][idx] =
+                Self::get_step_attacks(idx, &[(1, -1), (-1, -1)]);
+        }
+        for idx in 0..BOARD_ARRAY_SIZE {
+            res.rook[idx] = Self::init_magic(&mut res.sliding, idx, ROOK_MAGICS[idx], &ROOK_DIRS);
+            res.bishop[idx] =
+                Self::init_magic(&mut res.sliding, idx, BISHOP_MAGICS[idx], &BISHOP_DIRS);
+        }
+        res
+    }
+
+    fn get_step_attacks(idx: usize, steps: &[(i8, i8)]) -> Bitboard {
+        let pos = square_pos(idx);
+        let mut res = 0;
+        for (dx, dy) in steps {
+            let (x, y) = (pos.x as i8 + dx, pos.y as i8 + dy);
+            if ChessBoardState::coords_in_bounds(x, y) {
+                res |= square_bb(Pos::from_coords(x, y));
+            }
+        }
+        res
+    }
+
+    // slow ray walk, used only to fill the tables
+    fn get_ray_attacks(idx: usize, occupied: Bitboard, dirs: &[(i8, i8)]) -> Bitboard {
+        let pos = square_pos(idx);
+        let mut res = 0;
+        for (dx, dy) in dirs {
+            let (mut x, mut y) = (pos.x as i8 + dx, pos.y as i8 + dy);
+            while ChessBoardState::coords_in_bounds(x, y) {
+                let bb = square_bb(Pos::from_coords(x, y));
+                res |= bb;
+                if occupied & bb != 0 {
+                    break;
+                }
+                x += dx;
+                y += dy;
+            }
+        }
+        res
+    }
+
+    fn init_magic(sliding: &mut Vec<Bitboard>, idx: usize, magic: u64, dirs: &[(i8, i8)]) -> Magic {
+        // blockers on the board edge do not change attacks
+        let pos = square_pos(idx);
+        let edges = ((RANK_1 | RANK_1 << 56) & !(RANK_1 << (pos.y * 8)))
+            | ((FILE_A | FILE_A << 7) & !(FILE_A << pos.x));
+        let mask = Self::get_ray_attacks(idx, 0, dirs) & !edges;
+        let bits = mask.count_ones();
+        let res = Magic {
+            mask,
+            magic,
+            shift: 64 - bits,
+            offset: sliding.len(),
+        };
+        sliding.resize(sliding.len() + (1 << bits), 0);
+        // enumerate all subsets of the mask (Carry-Rippler)
+        let mut subset: Bitboard = 0;
+        loop {
+            sliding[res.get_index(subset)] = Self::get_ray_attacks(idx, subset, dirs);
+            subset = subset.wrapping_sub(mask) & mask;
+            if subset == 0 {
+                break;
+            }
+        }
+        res
+    }
+}
+
+impl ChessBoardState {
+    pub fn get_piece_bb(&self, piece: ChessPiece) -> Bitboard {
+        self.pieces_bb[piece as usize]
+    }
+
+    pub fn get_color_bb(&self, color: Color) -> Bitboard {
+        self.colors_bb[color as usize]
+    }
+
+    pub fn get_occupied_bb(&self) -> Bitboard {
+        self.colors_bb[0] | self.colors_bb[1]
+    }
+}
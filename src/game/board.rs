@@ -1,7 +1,12 @@
+use super::bitboard::*;
+
+use std::fmt;
+
 pub const BOARD_SIZE: usize = 8;
 pub const BOARD_ARRAY_SIZE: usize = BOARD_SIZE * BOARD_SIZE;
 pub type MoveCode = u16; // array of 4 4bit numbers
 pub type PosCode = u8;
+pub const START_POS_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -28,6 +33,33 @@ pub enum CastleStateFlag {
     BlackShort = 1 << 3,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FenField {
+    Board,
+    Turn,
+    Castling,
+    EnPassant,
+    HalfmoveClock,
+    MoveNumber,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    WrongRankCount(usize),
+    // ranks are numbered from 1 as in the notation
+    RankOverflow(u8),
+    RankTooShort(u8),
+    UnexpectedChar(FenField, char),
+    WrongNumber(FenField),
+    ImpossibleCastling(char),
+    ImpossibleEnPassant,
+    MissingKing(Color),
+    TooManyKings(Color),
+    PawnOnBackRank,
+    OpponentInCheck,
+}
+
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Color {
     White,
@@ -54,22 +86,31 @@ pub struct ChessBoardState {
     pub board: [ChessPiece; BOARD_ARRAY_SIZE],
     pub move_num: u16,
     pub halfmoves_to_draw: u8,
+    // Zobrist key, kept up to date by set_piece_unsafe and move application,
+    // recompute it after changing other fields directly
+    pub hash: u64,
+    // same pieces as in board as sets of squares, kept up to date by set_piece_unsafe,
+    // indexed by ChessPiece as u8 (empty squares for ChessPiece::None) and by Color as usize
+    pub pieces_bb: [Bitboard; 13],
+    pub colors_bb: [Bitboard; 2],
 }
 
 impl Pos {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(pos_str: &str) -> Self {
         Pos {
-            x: match pos_str.bytes().nth(0) {
-                Some(x) => x - b'a' as u8,
+            x: match pos_str.bytes().next() {
+                Some(x) => x - b'a',
                 None => 0xF,
             },
-            y: match pos_str.bytes().nth(1) {
-                Some(x) => x - b'1' as u8,
+            y: match pos_str.as_bytes().get(1) {
+                Some(x) => x - b'1',
                 None => 0xF,
             },
         }
     }
 
+    #[allow(clippy::needless_return)]
     pub fn from_code(code: PosCode) -> Self {
         return Pos {
             x: code & 0x0F,
@@ -78,22 +119,35 @@ impl Pos {
     }
 
     pub fn from_coords(x: i8, y: i8) -> Self {
-        Pos{x: x as u8, y: y as u8}
+        Pos {
+            x: x as u8,
+            y: y as u8,
+        }
     }
 
     pub fn get_code(&self) -> PosCode {
         (self.x & 0x0F) | (self.y << 4 & 0xF0)
     }
 
+    #[allow(clippy::needless_return)]
     pub fn get_str(&self) -> String {
         if self.x == 0xF && self.y == 0xF {
             return "-".to_string();
         }
         return String::from_utf8([self.x + b'a', self.y + b'1'].to_vec()).unwrap();
     }
+
+    // same square seen from the other side of the board
+    pub fn get_mirrored(&self) -> Pos {
+        Pos {
+            x: self.x,
+            y: BOARD_SIZE as u8 - 1 - self.y,
+        }
+    }
 }
 
 impl Move {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(move_str: &str) -> Self {
         Move {
             from: Pos::from_str(&move_str[0..2]),
@@ -101,6 +155,7 @@ impl Move {
         }
     }
 
+    #[allow(clippy::needless_return)]
     pub fn get_str(&self) -> String {
         return self.from.get_str() + "-" + &self.to.get_str();
     }
@@ -179,6 +234,25 @@ impl ChessPiece {
             Self::QueenBlack => Some(Color::Black),
         }
     }
+
+    // same kind of piece of the other color
+    pub fn get_opposite(&self) -> ChessPiece {
+        match *self {
+            Self::None => Self::None,
+            Self::PawnWhite => Self::PawnBlack,
+            Self::PawnBlack => Self::PawnWhite,
+            Self::RookWhite => Self::RookBlack,
+            Self::RookBlack => Self::RookWhite,
+            Self::KnightWhite => Self::KnightBlack,
+            Self::KnightBlack => Self::KnightWhite,
+            Self::BishopWhite => Self::BishopBlack,
+            Self::BishopBlack => Self::BishopWhite,
+            Self::KingWhite => Self::KingBlack,
+            Self::KingBlack => Self::KingWhite,
+            Self::QueenWhite => Self::QueenBlack,
+            Self::QueenBlack => Self::QueenWhite,
+        }
+    }
 }
 
 impl Color {
@@ -192,6 +266,14 @@ impl Color {
         }
     }
 
+    pub fn get_opposite(&self) -> Color {
+        if *self == Color::White {
+            Color::Black
+        } else {
+            Color::White
+        }
+    }
+
     pub fn get_name(&self) -> &'static str {
         if *self == Color::White {
             "White"
@@ -201,44 +283,111 @@ impl Color {
     }
 }
 
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount(x) => write!(f, "expected 4 to 6 fields, got {}", x),
+            FenError::WrongRankCount(x) => write!(f, "expected 8 ranks, got {}", x),
+            FenError::RankOverflow(x) => write!(f, "rank {} has more than 8 squares", x),
+            FenError::RankTooShort(x) => write!(f, "rank {} has less than 8 squares", x),
+            FenError::UnexpectedChar(field, c) => {
+                write!(f, "unexpected '{}' in {:?} field", c, field)
+            }
+            FenError::WrongNumber(field) => write!(f, "wrong number in {:?} field", field),
+            FenError::ImpossibleCastling(c) => {
+                write!(f, "castling right '{}' without king and rook in place", c)
+            }
+            FenError::ImpossibleEnPassant => write!(f, "impossible en passant square"),
+            FenError::MissingKing(x) => write!(f, "{} king is missing", x.get_name()),
+            FenError::TooManyKings(x) => write!(f, "more than one {} king", x.get_name()),
+            FenError::PawnOnBackRank => write!(f, "pawn on the first or last rank"),
+            FenError::OpponentInCheck => write!(f, "side not to move is in check"),
+        }
+    }
+}
+
 /*
 Such types for MoveCode, CastleStateFlag are required for less memory use when copying state for eval
  */
 
 impl ChessBoardState {
     // Constructors
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        ChessBoardState {
+        let mut res = ChessBoardState {
             turn: Color::White,
             en_passant: 0xFF,
             castle_state_flags: 0x00,
             board: [ChessPiece::None; BOARD_ARRAY_SIZE],
             move_num: 1,
             halfmoves_to_draw: 0,
-        }
+            hash: 0,
+            pieces_bb: [0; 13],
+            colors_bb: [0; 2],
+        };
+        res.pieces_bb[ChessPiece::None as usize] = !0;
+        res
     }
 
     pub fn from_fen(fen_str: &str) -> Option<Self> {
+        Self::try_from_fen(fen_str).ok()
+    }
+
+    pub fn try_from_fen(fen_str: &str) -> Result<Self, FenError> {
         let mut res = Self::new();
-        let r = res.parse_fen(fen_str);
-        if r.is_ok() {
-            Some(res)
-        } else {
-            None
+        res.parse_fen(fen_str)?;
+        Ok(res)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut res = String::new();
+        for y in (0..BOARD_SIZE).rev() {
+            let mut empty = 0;
+            for x in 0..BOARD_SIZE {
+                let piece = self.get_piece_coords_unsafe(x, y);
+                if piece == ChessPiece::None {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    res.push((b'0' + empty) as char);
+                    empty = 0;
+                }
+                res.push(piece.get_u8() as char);
+            }
+            if empty > 0 {
+                res.push((b'0' + empty) as char);
+            }
+            if y > 0 {
+                res.push('/');
+            }
         }
+
+        format!(
+            "{} {} {} {} {} {}",
+            res,
+            if self.turn == Color::White { 'w' } else { 'b' },
+            self.get_castle_state_str(),
+            Pos::from_code(self.en_passant).get_str(),
+            self.halfmoves_to_draw,
+            self.move_num
+        )
     }
 
-    // Getters 
-     pub fn get_piece_unsafe(&self, pos: Pos) -> ChessPiece {
+    // Getters
+    #[allow(clippy::needless_return)]
+    pub fn get_piece_unsafe(&self, pos: Pos) -> ChessPiece {
         return self.board[Self::get_pos_idx(pos)];
     }
+    #[allow(clippy::needless_return)]
     pub fn get_piece_coords_unsafe(&self, x: usize, y: usize) -> ChessPiece {
         return self.board[y * BOARD_SIZE + x];
     }
     pub fn get_piece_coords_i8_unsafe(&self, x: i8, y: i8) -> ChessPiece {
-        return self.board[(y as usize) * BOARD_SIZE  + x as usize];
+        self.board[(y as usize) * BOARD_SIZE + x as usize]
     }
 
+    #[allow(clippy::needless_return)]
     pub fn get_piece(&self, pos: Pos) -> Option<ChessPiece> {
         if pos.x >= BOARD_SIZE as u8 || pos.y >= BOARD_SIZE as u8 {
             return None;
@@ -248,33 +397,72 @@ impl ChessBoardState {
 
     // Setters
     pub fn set_piece_unsafe(&mut self, pos: Pos, piece: ChessPiece) {
-        self.board[Self::get_pos_idx(pos)] = piece;
+        let idx = Self::get_pos_idx(pos);
+        let old = self.board[idx];
+        self.hash ^= Self::get_piece_key(old, idx) ^ Self::get_piece_key(piece, idx);
+        self.pieces_bb[old as usize] &= !(1 << idx);
+        self.pieces_bb[piece as usize] |= 1 << idx;
+        if let Some(x) = old.get_color() {
+            self.colors_bb[x as usize] &= !(1 << idx);
+        }
+        if let Some(x) = piece.get_color() {
+            self.colors_bb[x as usize] |= 1 << idx;
+        }
+        self.board[idx] = piece;
     }
 
     // Public utils
+    #[allow(clippy::needless_return)]
     pub fn get_pos_idx(pos: Pos) -> usize {
         return (pos.y as usize) * BOARD_SIZE + pos.x as usize;
     }
 
+    #[allow(clippy::needless_return)]
     pub fn coords_in_bounds(x: i8, y: i8) -> bool {
         return x < BOARD_SIZE as i8 && y < BOARD_SIZE as i8 && x >= 0 && y >= 0;
     }
 
+    #[allow(clippy::needless_return)]
     pub fn pos_in_bounds(pos: Pos) -> bool {
         return pos.x < BOARD_SIZE as u8 && pos.y < BOARD_SIZE as u8;
     }
 
+    // same position with colors swapped and ranks flipped, side to move is swapped too,
+    // so it must be exactly as good for the new side to move as the original was for the old one
+    pub fn mirror(&self) -> ChessBoardState {
+        let mut res = Self::new();
+        for (i, piece) in self.board.iter().enumerate() {
+            res.set_piece_unsafe(square_pos(i).get_mirrored(), piece.get_opposite());
+        }
+        res.turn = self.turn.get_opposite();
+        // white flags are in the two lowest bits, black ones in the next two
+        res.castle_state_flags =
+            (self.castle_state_flags & 0b0011) << 2 | (self.castle_state_flags & 0b1100) >> 2;
+        if self.en_passant != 0xFF {
+            res.en_passant = Pos::from_code(self.en_passant).get_mirrored().get_code();
+        }
+        res.move_num = self.move_num;
+        res.halfmoves_to_draw = self.halfmoves_to_draw;
+        res.hash = res.compute_hash();
+        res
+    }
+
+    // same position for repetition rules, move counters are ignored
+    pub fn is_same_position(&self, other: &ChessBoardState) -> bool {
+        self.hash == other.hash
+    }
+
     // Debug
     pub fn debug_print(&self) {
         for i in 0..self.board.len() {
             if i > 0 && i % 8 == 0 {
-                print!(" | {}\n", 9 - i / 8);
+                println!(" | {}", 9 - i / 8);
             }
             print!("{} ", self.board[Self::get_display_idx(i)].get_char())
         }
-        print!(" | 1\n");
-        print!("----------------\n");
-        print!("a b c d e f g h \n");
+        println!(" | 1");
+        println!("----------------");
+        println!("a b c d e f g h ");
         print!(
             "\nTurn {}, en passant {}, castle {}\n\n",
             self.turn.get_name(),
@@ -284,105 +472,200 @@ impl ChessBoardState {
     }
 
     // Internal utils
+    // castling field of FEN, KQkq order
+    #[allow(clippy::needless_return)]
     fn get_castle_state_str(&self) -> String {
-
         if self.castle_state_flags == 0 {
             return "-".to_string();
         }
 
         let mut res = vec![];
 
-        if (&self.castle_state_flags & CastleStateFlag::WhiteShort as u8) != 0 {
+        if (self.castle_state_flags & CastleStateFlag::WhiteShort as u8) != 0 {
             res.push(b'K');
         }
-        if (&self.castle_state_flags & CastleStateFlag::BlackShort as u8) != 0 {
-            res.push(b'k');
-        }
-        if (&self.castle_state_flags & CastleStateFlag::WhiteLong as u8) != 0 {
+        if (self.castle_state_flags & CastleStateFlag::WhiteLong as u8) != 0 {
             res.push(b'Q');
         }
-        if (&self.castle_state_flags & CastleStateFlag::BlackLong as u8) != 0 {
+        if (self.castle_state_flags & CastleStateFlag::BlackShort as u8) != 0 {
+            res.push(b'k');
+        }
+        if (self.castle_state_flags & CastleStateFlag::BlackLong as u8) != 0 {
             res.push(b'q');
         }
 
         return String::from_utf8(res).unwrap();
     }
 
+    #[allow(clippy::needless_return)]
     fn get_display_idx(i: usize) -> usize {
         return 8 * (7 - i / 8) + i % 8;
     }
 
-    fn parse_board(&mut self, fen_str: &str) -> usize {
-        let mut cur_idx: usize = 0;
-        for i in 0..fen_str.len() {
-            let cur_byte = fen_str.bytes().nth(i);
-            match cur_byte {
-                None => {
-                    return usize::MAX;
-                }
-                Some(x) => match x {
-                    b'/' => {}
-                    b'0'..=b'9' => cur_idx += (x - b'0') as usize,
-                    b' ' => {
-                        return i;
-                    }
+    fn parse_board(&mut self, board_str: &str) -> Result<(), FenError> {
+        let ranks: Vec<&str> = board_str.split('/').collect();
+        if ranks.len() != BOARD_SIZE {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+        for (i, rank_str) in ranks.iter().enumerate() {
+            let y = (BOARD_SIZE - 1 - i) as u8;
+            let mut x: usize = 0;
+            for c in rank_str.bytes() {
+                match c {
+                    b'1'..=b'8' => x += (c - b'0') as usize,
                     _ => {
-                        self.board[Self::get_display_idx(cur_idx)] =
-                            ChessPiece::from_u8(x);
-                        cur_idx += 1;
+                        let piece = ChessPiece::from_u8(c);
+                        if piece == ChessPiece::None {
+                            return Err(FenError::UnexpectedChar(FenField::Board, c as char));
+                        }
+                        if x < BOARD_SIZE {
+                            self.set_piece_unsafe(Pos { x: x as u8, y }, piece);
+                        }
+                        x += 1;
                     }
-                },
+                }
+                if x > BOARD_SIZE {
+                    return Err(FenError::RankOverflow(y + 1));
+                }
+            }
+            if x < BOARD_SIZE {
+                return Err(FenError::RankTooShort(y + 1));
             }
         }
-        return usize::MAX;
+        Ok(())
     }
 
-    fn parse_fen(&mut self, fen_str: &str) -> Result<(), String> {
-        use scan_fmt::scan_fmt;
-        let parsed_str_opt = scan_fmt!(fen_str, "{}{}{}{}{}{}", String, char, String, String, u8, u16);
-        if parsed_str_opt.is_err() {
-            return Err("Wrong format".to_string());
+    fn parse_fen(&mut self, fen_str: &str) -> Result<(), FenError> {
+        let fields: Vec<&str> = fen_str.split_whitespace().collect();
+        // halfmove clock and move number are often omitted (EPD)
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(FenError::WrongFieldCount(fields.len()));
         }
-        let parsed_values = parsed_str_opt.unwrap();
-        self.parse_board(&parsed_values.0);
-        match Color::from_u8(parsed_values.1 as u8) {
-            None => {
-                return Err("Wrong color".to_string());
-            }
-            Some(x) => {
-                self.turn = x;
+        self.parse_board(fields[0])?;
+
+        let turn = fields[1].as_bytes();
+        match Color::from_u8(turn[0]) {
+            Some(x) if turn.len() == 1 => self.turn = x,
+            _ => {
+                return Err(FenError::UnexpectedChar(
+                    FenField::Turn,
+                    fields[1].chars().next().unwrap(),
+                ))
             }
         }
-        for i in parsed_values.2.as_bytes() {
-            match i {
-                b'K' => {
-                    self.castle_state_flags |= CastleStateFlag::WhiteShort as u8;
-                }
-                b'k' => {
-                    self.castle_state_flags |= CastleStateFlag::BlackShort as u8;
-                }
-                b'Q' => {
-                    self.castle_state_flags |= CastleStateFlag::WhiteLong as u8;
-                }
-                b'q' => {
-                    self.castle_state_flags |= CastleStateFlag::BlackLong as u8;
-                }
-                b'-' => {
-                    self.castle_state_flags = 0;
-                    break;
+
+        if fields[2] != "-" {
+            for c in fields[2].bytes() {
+                let flag = match c {
+                    b'K' => CastleStateFlag::WhiteShort,
+                    b'k' => CastleStateFlag::BlackShort,
+                    b'Q' => CastleStateFlag::WhiteLong,
+                    b'q' => CastleStateFlag::BlackLong,
+                    _ => return Err(FenError::UnexpectedChar(FenField::Castling, c as char)),
+                };
+                if !self.castle_rights_possible(c) {
+                    return Err(FenError::ImpossibleCastling(c as char));
                 }
-                _ => {
-                    return Err("Wrong casle format".to_string());
+                self.castle_state_flags |= flag as u8;
+            }
+        }
+
+        if fields[3] != "-" {
+            let ep = fields[3].as_bytes();
+            for (i, c) in ep.iter().enumerate() {
+                let ok = match i {
+                    0 => (b'a'..=b'h').contains(c),
+                    1 => (b'1'..=b'8').contains(c),
+                    _ => false,
+                };
+                if !ok {
+                    return Err(FenError::UnexpectedChar(FenField::EnPassant, *c as char));
                 }
             }
+            if ep.len() != 2 {
+                return Err(FenError::ImpossibleEnPassant);
+            }
+            let pos = Pos::from_str(fields[3]);
+            if !self.en_passant_possible(pos) {
+                return Err(FenError::ImpossibleEnPassant);
+            }
+            self.en_passant = pos.get_code();
+            // kept only if it can be taken on, as after a double push in make_move
+            if !self.has_legal_en_passant() {
+                self.en_passant = 0xFF;
+            }
+        }
+
+        if let Some(x) = fields.get(4) {
+            self.halfmoves_to_draw = x
+                .parse()
+                .map_err(|_| FenError::WrongNumber(FenField::HalfmoveClock))?;
         }
-        if parsed_values.3 != "-" {
-            self.en_passant = Pos::from_str(&parsed_values.3).get_code();
+        if let Some(x) = fields.get(5) {
+            self.move_num = x
+                .parse()
+                .map_err(|_| FenError::WrongNumber(FenField::MoveNumber))?;
+            if self.move_num == 0 {
+                return Err(FenError::WrongNumber(FenField::MoveNumber));
+            }
         }
 
-        self.halfmoves_to_draw = parsed_values.4;
-        self.move_num = parsed_values.5;
+        self.hash = self.compute_hash();
+        self.validate_position()
+    }
+
+    fn castle_rights_possible(&self, c: u8) -> bool {
+        let (king, rook, y, rook_x) = match c {
+            b'K' => (ChessPiece::KingWhite, ChessPiece::RookWhite, 0, 7),
+            b'Q' => (ChessPiece::KingWhite, ChessPiece::RookWhite, 0, 0),
+            b'k' => (ChessPiece::KingBlack, ChessPiece::RookBlack, 7, 7),
+            _ => (ChessPiece::KingBlack, ChessPiece::RookBlack, 7, 0),
+        };
+        self.get_piece_coords_unsafe(4, y) == king
+            && self.get_piece_coords_unsafe(rook_x, y) == rook
+    }
 
+    // square behind a pawn that has just moved two squares
+    fn en_passant_possible(&self, pos: Pos) -> bool {
+        let (y, pawn_y, from_y, pawn) = if self.turn == Color::White {
+            (5, 4, 6, ChessPiece::PawnBlack)
+        } else {
+            (2, 3, 1, ChessPiece::PawnWhite)
+        };
+        let x = pos.x as usize;
+        pos.y == y
+            && self.get_piece_coords_unsafe(x, pawn_y) == pawn
+            && self.get_piece_coords_unsafe(x, y as usize) == ChessPiece::None
+            && self.get_piece_coords_unsafe(x, from_y) == ChessPiece::None
+    }
+
+    fn validate_position(&self) -> Result<(), FenError> {
+        for (color, king) in [
+            (Color::White, ChessPiece::KingWhite),
+            (Color::Black, ChessPiece::KingBlack),
+        ] {
+            match self.board.iter().filter(|x| **x == king).count() {
+                0 => return Err(FenError::MissingKing(color)),
+                1 => {}
+                _ => return Err(FenError::TooManyKings(color)),
+            }
+        }
+        for x in 0..BOARD_SIZE {
+            for y in [0, BOARD_SIZE - 1] {
+                let piece = self.get_piece_coords_unsafe(x, y);
+                if piece == ChessPiece::PawnWhite || piece == ChessPiece::PawnBlack {
+                    return Err(FenError::PawnOnBackRank);
+                }
+            }
+        }
+        let opponent = if self.turn == Color::White {
+            Color::Black
+        } else {
+            Color::White
+        };
+        if self.get_king_attacked(opponent) {
+            return Err(FenError::OpponentInCheck);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,123 @@
+use super::board::*;
+use super::rules::*;
+use super::status::*;
+
+// Game played from some start position, knows how the current position was reached
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    // positions[0] is the start position, positions[i + 1] is position after moves[i]
+    positions: Vec<ChessBoardState>,
+    moves: Vec<ChessMove>,
+    // moves taken back by undo, last one is redone first
+    undone: Vec<ChessMove>,
+}
+
+impl Game {
+    pub fn new(start: ChessBoardState) -> Self {
+        Game {
+            positions: vec![start],
+            moves: vec![],
+            undone: vec![],
+        }
+    }
+
+    pub fn from_fen(fen_str: &str) -> Option<Self> {
+        ChessBoardState::from_fen(fen_str).map(Self::new)
+    }
+
+    pub fn get_board(&self) -> &ChessBoardState {
+        self.positions.last().unwrap()
+    }
+
+    pub fn get_start(&self) -> &ChessBoardState {
+        &self.positions[0]
+    }
+
+    pub fn get_moves(&self) -> &[ChessMove] {
+        &self.moves
+    }
+
+    // all positions from the start one to the current one
+    pub fn get_positions(&self) -> &[ChessBoardState] {
+        &self.positions
+    }
+
+    // positions before the current one, as Evaluator::set_history expects them
+    pub fn get_previous_positions(&self) -> &[ChessBoardState] {
+        &self.positions[..self.positions.len() - 1]
+    }
+
+    // returns false and does nothing if move is illegal
+    pub fn push(&mut self, mv: ChessMove) -> bool {
+        if !self.get_board().get_all_moves_checked().contains(&mv) {
+            return false;
+        }
+        self.push_unchecked(mv);
+        self.undone.clear();
+        true
+    }
+
+    // removes last move, it can not be redone
+    pub fn pop(&mut self) -> Option<ChessMove> {
+        let mv = self.moves.pop()?;
+        self.positions.pop();
+        Some(mv)
+    }
+
+    pub fn undo(&mut self) -> Option<ChessMove> {
+        let mv = self.pop()?;
+        self.undone.push(mv);
+        Some(mv)
+    }
+
+    pub fn redo(&mut self) -> Option<ChessMove> {
+        let mv = self.undone.pop()?;
+        self.push_unchecked(mv);
+        Some(mv)
+    }
+
+    // how many times current position occurred, counting the current one
+    pub fn get_repetition_count(&self) -> usize {
+        let board = self.get_board();
+        // positions before a capture or pawn move can not repeat
+        let reversible = (board.halfmoves_to_draw as usize).min(self.positions.len() - 1);
+        self.positions
+            .iter()
+            .rev()
+            .take(reversible + 1)
+            .step_by(2)
+            .filter(|x| x.is_same_position(board))
+            .count()
+    }
+
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.get_repetition_count() >= 3
+    }
+
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.get_repetition_count() >= 5
+    }
+
+    // ChessBoardState::get_game_status extended by repetition rules
+    pub fn get_status(&self) -> GameStatus {
+        let status = self.get_board().get_game_status();
+        match status {
+            GameStatus::Checkmate(_) | GameStatus::Stalemate => return status,
+            _ => {}
+        }
+        let repetitions = self.get_repetition_count();
+        if repetitions >= 5 {
+            return GameStatus::FivefoldRepetition;
+        }
+        if status == GameStatus::Ongoing && repetitions >= 3 {
+            return GameStatus::ThreefoldRepetition;
+        }
+        status
+    }
+
+    fn push_unchecked(&mut self, mv: ChessMove) {
+        let board = self.get_board().get_new_pos_after_move(mv);
+        self.positions.push(board);
+        self.moves.push(mv);
+    }
+}
@@ -1,2 +1,12 @@
 pub mod board;
-pub mod rules;
\ No newline at end of file
+pub mod rules;
+pub mod san;
+pub mod pgn;
+pub mod perft;
+pub mod status;
+pub mod history;
+pub mod zobrist;
+pub mod bitboard;
+pub mod movegen;
+pub mod packed_move;
+pub mod see;
@@ -0,0 +1,363 @@
+use super::bitboard::*;
+use super::board::*;
+use super::packed_move::*;
+use super::rules::*;
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+// more than legal moves in any reachable position (218 is the known maximum)
+pub const MAX_MOVES: usize = 256;
+
+// fixed capacity buffer on the stack, so move generation does not allocate
+#[derive(Clone, Copy)]
+pub struct MoveList {
+    moves: [PackedMove; MAX_MOVES],
+    len: usize,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum GenType {
+    All,
+    // captures, en passant and all promotions
+    Captures,
+    // everything else, so captures and quiets together are all legal moves
+    Quiets,
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        MoveList {
+            moves: [PackedMove::NULL; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: PackedMove) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [PackedMove];
+
+    fn deref(&self) -> &[PackedMove] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [PackedMove] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a PackedMove;
+    type IntoIter = std::slice::Iter<'a, PackedMove>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl ChessBoardState {
+    // Legal move generation: checkers and pins are found once, so no move
+    // has to be made to see whether it leaves the king attacked
+    pub fn get_legal_moves(&self) -> MoveList {
+        self.generate_legal(GenType::All)
+    }
+
+    pub fn get_legal_captures(&self) -> MoveList {
+        self.generate_legal(GenType::Captures)
+    }
+
+    pub fn get_legal_quiets(&self) -> MoveList {
+        self.generate_legal(GenType::Quiets)
+    }
+
+    // pieces of given color attacking the square, as if only squares in occupied were taken
+    pub fn get_attackers(&self, idx: usize, color: Color, occupied: Bitboard) -> Bitboard {
+        let pieces = |white, black| self.get_piece_bb(white) | self.get_piece_bb(black);
+        let queens = pieces(ChessPiece::QueenWhite, ChessPiece::QueenBlack);
+        let res = pawn_attacks(color.get_opposite(), idx)
+            & pieces(ChessPiece::PawnWhite, ChessPiece::PawnBlack)
+            | knight_attacks(idx) & pieces(ChessPiece::KnightWhite, ChessPiece::KnightBlack)
+            | king_attacks(idx) & pieces(ChessPiece::KingWhite, ChessPiece::KingBlack)
+            | bishop_attacks(idx, occupied)
+                & (pieces(ChessPiece::BishopWhite, ChessPiece::BishopBlack) | queens)
+            | rook_attacks(idx, occupied)
+                & (pieces(ChessPiece::RookWhite, ChessPiece::RookBlack) | queens);
+        res & self.get_color_bb(color) & occupied
+    }
+
+    // en passant square is a part of the position only if side to move can really take on it,
+    // checked once when the square is set
+    pub fn has_legal_en_passant(&self) -> bool {
+        if self.en_passant == 0xFF {
+            return false;
+        }
+        let color = self.turn;
+        let en_passant = Pos::from_code(self.en_passant);
+        let to = Self::get_pos_idx(en_passant);
+        let pawn = if color == Color::White {
+            ChessPiece::PawnWhite
+        } else {
+            ChessPiece::PawnBlack
+        };
+        let mut pawns = pawn_attacks(color.get_opposite(), to) & self.get_piece_bb(pawn);
+        if pawns == 0 {
+            return false;
+        }
+        let king_bb = self.get_color_bb(color)
+            & (self.get_piece_bb(ChessPiece::KingWhite) | self.get_piece_bb(ChessPiece::KingBlack));
+        if king_bb == 0 {
+            return true;
+        }
+        let king = king_bb.trailing_zeros() as usize;
+        while pawns != 0 {
+            let from = pop_lsb(&mut pawns);
+            let captured = Pos::from_coords(en_passant.x as i8, square_pos(from).y as i8);
+            let occupied =
+                self.get_occupied_bb() ^ 1 << from ^ square_bb(captured) | square_bb(en_passant);
+            if self.get_attackers(king, color.get_opposite(), occupied) == 0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn generate_legal(&self, gen_type: GenType) -> MoveList {
+        let mut res = MoveList::new();
+        let color = self.turn;
+        let them = color.get_opposite();
+        let own_bb = self.get_color_bb(color);
+        let them_bb = self.get_color_bb(them);
+        let occupied = own_bb | them_bb;
+        let king_bb = own_bb
+            & (self.get_piece_bb(ChessPiece::KingWhite) | self.get_piece_bb(ChessPiece::KingBlack));
+        if king_bb == 0 {
+            return res;
+        }
+        let king = king_bb.trailing_zeros() as usize;
+
+        // non king moves must capture the checker or block it, double check leaves only king moves
+        let checkers = self.get_attackers(king, them, occupied);
+        let check_mask = match checkers.count_ones() {
+            0 => !0,
+            1 => checkers | between_bb(king, checkers.trailing_zeros() as usize),
+            _ => 0,
+        };
+
+        // own piece alone between king and enemy slider can only move along that line
+        let mut pin_rays = [!0 as Bitboard; BOARD_ARRAY_SIZE];
+        let queens =
+            self.get_piece_bb(ChessPiece::QueenWhite) | self.get_piece_bb(ChessPiece::QueenBlack);
+        let rooks =
+            self.get_piece_bb(ChessPiece::RookWhite) | self.get_piece_bb(ChessPiece::RookBlack);
+        let bishops =
+            self.get_piece_bb(ChessPiece::BishopWhite) | self.get_piece_bb(ChessPiece::BishopBlack);
+        let mut snipers = them_bb
+            & (rook_attacks(king, 0) & (rooks | queens)
+                | bishop_attacks(king, 0) & (bishops | queens));
+        while snipers != 0 {
+            let sniper = pop_lsb(&mut snipers);
+            let ray = between_bb(king, sniper);
+            let blockers = ray & occupied;
+            if blockers.count_ones() == 1 && blockers & own_bb != 0 {
+                pin_rays[blockers.trailing_zeros() as usize] = ray | 1 << sniper;
+            }
+        }
+
+        let target_mask = match gen_type {
+            GenType::All => !own_bb,
+            GenType::Captures => them_bb,
+            GenType::Quiets => !occupied,
+        };
+
+        let mut own = own_bb;
+        while own != 0 {
+            let idx = pop_lsb(&mut own);
+            let from = square_pos(idx);
+            let targets = match self.get_piece_unsafe(from) {
+                ChessPiece::KingWhite | ChessPiece::KingBlack => {
+                    if gen_type != GenType::Captures && checkers == 0 {
+                        self.add_legal_castle_moves(from, &mut res);
+                    }
+                    let mut targets = king_attacks(idx) & target_mask;
+                    while targets != 0 {
+                        let to = pop_lsb(&mut targets);
+                        // king must not hide behind itself from a slider
+                        if self.get_attackers(to, them, occupied ^ king_bb) == 0 {
+                            res.push(Self::get_simple_move(from, to, them_bb));
+                        }
+                    }
+                    continue;
+                }
+                _ if check_mask == 0 => continue,
+                ChessPiece::PawnWhite | ChessPiece::PawnBlack => {
+                    self.add_legal_pawn_moves(
+                        idx,
+                        gen_type,
+                        check_mask & pin_rays[idx],
+                        king,
+                        &mut res,
+                    );
+                    continue;
+                }
+                ChessPiece::KnightWhite | ChessPiece::KnightBlack => knight_attacks(idx),
+                ChessPiece::BishopWhite | ChessPiece::BishopBlack => bishop_attacks(idx, occupied),
+                ChessPiece::RookWhite | ChessPiece::RookBlack => rook_attacks(idx, occupied),
+                _ => queen_attacks(idx, occupied),
+            };
+            let mut targets = targets & target_mask & check_mask & pin_rays[idx];
+            while targets != 0 {
+                res.push(Self::get_simple_move(from, pop_lsb(&mut targets), them_bb));
+            }
+        }
+        res
+    }
+
+    fn get_simple_move(from: Pos, to: usize, them_bb: Bitboard) -> PackedMove {
+        let mv = ChessMove {
+            mv: Move {
+                from,
+                to: square_pos(to),
+            },
+            move_type: ChessMoveType::Simple,
+        };
+        PackedMove::new(mv, them_bb & 1 << to != 0)
+    }
+
+    fn add_legal_castle_moves(&self, from: Pos, res: &mut MoveList) {
+        let them = self.turn.get_opposite();
+        let (short, long) = if self.turn == Color::White {
+            (CastleStateFlag::WhiteShort, CastleStateFlag::WhiteLong)
+        } else {
+            (CastleStateFlag::BlackShort, CastleStateFlag::BlackLong)
+        };
+        for (flag, dir, move_type) in [
+            (short, 1, ChessMoveType::CastleShort),
+            (long, -1, ChessMoveType::CastleLong),
+        ] {
+            if self.castle_state_flags & flag as u8 == 0 || !self.check_castle(from, dir) {
+                continue;
+            }
+            let to = Pos::from_coords(from.x as i8 + 2 * dir, from.y as i8);
+            if self.get_attackers(Self::get_pos_idx(to), them, self.get_occupied_bb()) == 0 {
+                let mv = ChessMove {
+                    mv: Move { from, to },
+                    move_type,
+                };
+                res.push(PackedMove::new(mv, false));
+            }
+        }
+    }
+
+    // mask already contains check and pin restrictions
+    fn add_legal_pawn_moves(
+        &self,
+        idx: usize,
+        gen_type: GenType,
+        mask: Bitboard,
+        king: usize,
+        res: &mut MoveList,
+    ) {
+        let from = square_pos(idx);
+        let color = self.turn;
+        let them_bb = self.get_color_bb(color.get_opposite());
+        let empty = self.get_piece_bb(ChessPiece::None);
+        let (step, start_y, last_y): (i8, u8, u8) = if color == Color::White {
+            (1, 1, 7)
+        } else {
+            (-1, 6, 0)
+        };
+
+        let mut targets = pawn_attacks(color, idx) & them_bb;
+        let one = Pos::from_coords(from.x as i8, from.y as i8 + step);
+        if Self::pos_in_bounds(one) && square_bb(one) & empty != 0 {
+            targets |= square_bb(one);
+            let two = Pos::from_coords(from.x as i8, from.y as i8 + 2 * step);
+            if from.y == start_y && square_bb(two) & empty != 0 {
+                targets |= square_bb(two);
+            }
+        }
+        targets &= mask;
+
+        let proms = if color == Color::White {
+            [
+                ChessPiece::RookWhite,
+                ChessPiece::QueenWhite,
+                ChessPiece::BishopWhite,
+                ChessPiece::KnightWhite,
+            ]
+        } else {
+            [
+                ChessPiece::RookBlack,
+                ChessPiece::QueenBlack,
+                ChessPiece::BishopBlack,
+                ChessPiece::KnightBlack,
+            ]
+        };
+        while targets != 0 {
+            let to_idx = pop_lsb(&mut targets);
+            let to = square_pos(to_idx);
+            let capture = them_bb & 1 << to_idx != 0;
+            let noisy = to.y == last_y || capture;
+            if gen_type == GenType::Captures && !noisy || gen_type == GenType::Quiets && noisy {
+                continue;
+            }
+            if to.y != last_y {
+                res.push(Self::get_simple_move(from, to_idx, them_bb));
+                continue;
+            }
+            for prom_piece in proms {
+                let mv = ChessMove {
+                    mv: Move { from, to },
+                    move_type: ChessMoveType::Promotion(prom_piece),
+                };
+                res.push(PackedMove::new(mv, capture));
+            }
+        }
+
+        if gen_type == GenType::Quiets || self.en_passant == 0xFF {
+            return;
+        }
+        let en_passant = Pos::from_code(self.en_passant);
+        if pawn_attacks(color, idx) & square_bb(en_passant) == 0 {
+            return;
+        }
+        // both pawns leave their squares at once, which can uncover the king along the rank,
+        // so the move is checked on the board occupancy after it
+        let captured = Pos::from_coords(en_passant.x as i8, from.y as i8);
+        let occupied =
+            self.get_occupied_bb() ^ 1 << idx ^ square_bb(captured) | square_bb(en_passant);
+        if self.get_attackers(king, color.get_opposite(), occupied) == 0 {
+            let mv = ChessMove {
+                mv: Move {
+                    from,
+                    to: en_passant,
+                },
+                move_type: ChessMoveType::EnPassant,
+            };
+            res.push(PackedMove::new(mv, true));
+        }
+    }
+}
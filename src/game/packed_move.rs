@@ -0,0 +1,141 @@
+use super::bitboard::*;
+use super::board::*;
+use super::rules::*;
+
+// Move in 16 bits: from square index in bits 0-5, to square index in bits 6-11
+// and kind of move in bits 12-15. Zero (a1a1) is never a legal move
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct PackedMove(MoveCode);
+
+const FLAG_QUIET: u16 = 0;
+const FLAG_CAPTURE: u16 = 1;
+const FLAG_CASTLE_SHORT: u16 = 2;
+const FLAG_CASTLE_LONG: u16 = 3;
+const FLAG_EN_PASSANT: u16 = 4;
+// promotion flags keep the piece in two lowest bits and capture in the third one
+const FLAG_PROMOTION: u16 = 8;
+const FLAG_PROMOTION_CAPTURE: u16 = 4;
+
+impl PackedMove {
+    pub const NULL: PackedMove = PackedMove(0);
+
+    // capture is not known from ChessMove alone, see ChessBoardState::pack_move
+    pub fn new(mv: ChessMove, capture: bool) -> Self {
+        let flags = match mv.move_type {
+            ChessMoveType::Simple if capture => FLAG_CAPTURE,
+            ChessMoveType::Simple => FLAG_QUIET,
+            ChessMoveType::EnPassant => FLAG_EN_PASSANT,
+            ChessMoveType::CastleShort => FLAG_CASTLE_SHORT,
+            ChessMoveType::CastleLong => FLAG_CASTLE_LONG,
+            ChessMoveType::Promotion(x) => {
+                let piece = match x {
+                    ChessPiece::KnightWhite | ChessPiece::KnightBlack => 0,
+                    ChessPiece::BishopWhite | ChessPiece::BishopBlack => 1,
+                    ChessPiece::RookWhite | ChessPiece::RookBlack => 2,
+                    _ => 3,
+                };
+                FLAG_PROMOTION | piece | if capture { FLAG_PROMOTION_CAPTURE } else { 0 }
+            }
+        };
+        let from = ChessBoardState::get_pos_idx(mv.mv.from) as u16;
+        let to = ChessBoardState::get_pos_idx(mv.mv.to) as u16;
+        PackedMove(from | to << 6 | flags << 12)
+    }
+
+    pub fn from_code(code: MoveCode) -> Self {
+        PackedMove(code)
+    }
+
+    pub fn get_code(&self) -> MoveCode {
+        self.0
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn get_from(&self) -> Pos {
+        square_pos((self.0 & 0x3F) as usize)
+    }
+
+    pub fn get_to(&self) -> Pos {
+        square_pos((self.0 >> 6 & 0x3F) as usize)
+    }
+
+    // board index of the square, for tables indexed by squares
+    pub fn get_from_idx(&self) -> usize {
+        (self.0 & 0x3F) as usize
+    }
+
+    pub fn get_to_idx(&self) -> usize {
+        (self.0 >> 6 & 0x3F) as usize
+    }
+
+    fn get_flags(&self) -> u16 {
+        self.0 >> 12
+    }
+
+    pub fn is_capture(&self) -> bool {
+        let flags = self.get_flags();
+        if flags & FLAG_PROMOTION != 0 {
+            return flags & FLAG_PROMOTION_CAPTURE != 0;
+        }
+        flags == FLAG_CAPTURE || flags == FLAG_EN_PASSANT
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        self.get_flags() & FLAG_PROMOTION != 0
+    }
+
+    pub fn unpack(&self) -> ChessMove {
+        let mv = Move {
+            from: self.get_from(),
+            to: self.get_to(),
+        };
+        let flags = self.get_flags();
+        let move_type = match flags {
+            FLAG_EN_PASSANT => ChessMoveType::EnPassant,
+            FLAG_CASTLE_SHORT => ChessMoveType::CastleShort,
+            FLAG_CASTLE_LONG => ChessMoveType::CastleLong,
+            _ if flags & FLAG_PROMOTION != 0 => {
+                // color of promoted piece is known from the last rank
+                let white = mv.to.y == BOARD_SIZE as u8 - 1;
+                let piece = match (flags & 3, white) {
+                    (0, true) => ChessPiece::KnightWhite,
+                    (0, false) => ChessPiece::KnightBlack,
+                    (1, true) => ChessPiece::BishopWhite,
+                    (1, false) => ChessPiece::BishopBlack,
+                    (2, true) => ChessPiece::RookWhite,
+                    (2, false) => ChessPiece::RookBlack,
+                    (_, true) => ChessPiece::QueenWhite,
+                    (_, false) => ChessPiece::QueenBlack,
+                };
+                ChessMoveType::Promotion(piece)
+            }
+            _ => ChessMoveType::Simple,
+        };
+        ChessMove { mv, move_type }
+    }
+
+    pub fn get_uci_string(&self) -> String {
+        self.unpack().get_uci_string()
+    }
+
+    // same move in the position from ChessBoardState::mirror, flags do not depend on color
+    pub fn get_mirrored(&self) -> PackedMove {
+        PackedMove(self.0 ^ (56 | 56 << 6))
+    }
+}
+
+impl From<PackedMove> for ChessMove {
+    fn from(mv: PackedMove) -> Self {
+        mv.unpack()
+    }
+}
+
+impl ChessBoardState {
+    // packed form of a move in this position, with capture flag taken from the board
+    pub fn pack_move(&self, mv: ChessMove) -> PackedMove {
+        PackedMove::new(mv, self.get_piece_unsafe(mv.mv.to) != ChessPiece::None)
+    }
+}
@@ -0,0 +1,91 @@
+use super::board::*;
+use super::rules::*;
+
+// counters of the leaf moves, named as in the usual perft tables
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passants: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub checkmates: u64,
+}
+
+impl PerftStats {
+    fn add(&mut self, other: &PerftStats) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passants += other.en_passants;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+impl ChessBoardState {
+    // number of legal move sequences of given length
+    pub fn perft(&self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.get_legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .iter()
+            .map(|mv| self.get_new_pos_after_move(mv.unpack()).perft(depth - 1))
+            .sum()
+    }
+
+    // perft count for every root move, sorted as long algebraic strings
+    pub fn divide(&self, depth: usize) -> Vec<(ChessMove, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
+        let mut res: Vec<(ChessMove, u64)> = self
+            .get_legal_moves()
+            .iter()
+            .map(|x| x.unpack())
+            .map(|mv| (mv, self.get_new_pos_after_move(mv).perft(depth - 1)))
+            .collect();
+        res.sort_by_key(|(mv, _)| mv.get_uci_string());
+        res
+    }
+
+    pub fn perft_stats(&self, depth: usize) -> PerftStats {
+        let mut res = PerftStats::default();
+        if depth == 0 {
+            res.nodes = 1;
+            return res;
+        }
+        for packed in self.get_legal_moves().iter() {
+            let mv = packed.unpack();
+            let new_board = self.get_new_pos_after_move(mv);
+            if depth > 1 {
+                res.add(&new_board.perft_stats(depth - 1));
+                continue;
+            }
+            res.nodes += 1;
+            if packed.is_capture() {
+                res.captures += 1;
+            }
+            match mv.move_type {
+                ChessMoveType::EnPassant => res.en_passants += 1,
+                ChessMoveType::CastleLong | ChessMoveType::CastleShort => res.castles += 1,
+                ChessMoveType::Promotion(_) => res.promotions += 1,
+                ChessMoveType::Simple => {}
+            }
+            if new_board.get_king_attacked(new_board.turn) {
+                res.checks += 1;
+                if new_board.get_legal_moves().is_empty() {
+                    res.checkmates += 1;
+                }
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,559 @@
+use super::board::*;
+use super::rules::*;
+
+use std::fmt;
+
+// tags that must be written first and in this order
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const MAX_LINE_LEN: usize = 79;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnMove {
+    pub mv: ChessMove,
+    // position after the move
+    pub board: ChessBoardState,
+    pub nags: Vec<u8>,
+    pub comment_before: Option<String>,
+    pub comment_after: Option<String>,
+    // alternatives to this move, each starts from position before it
+    pub variations: Vec<PgnVariation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PgnVariation {
+    pub moves: Vec<PgnMove>,
+    // comment of a variation without moves, "( {comment} )"
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub start: ChessBoardState,
+    pub moves: Vec<PgnMove>,
+    // "1-0", "0-1", "1/2-1/2" or "*"
+    pub result: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    TagPair(String, String),
+    Comment(String),
+    MoveNumber,
+    Nag(u8),
+    VariationStart,
+    VariationEnd,
+    GameResult(String),
+    San(String),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize, usize)>,
+    idx: usize,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl PgnMove {
+    pub fn new(mv: ChessMove, board: ChessBoardState) -> Self {
+        PgnMove {
+            mv,
+            board,
+            nags: vec![],
+            comment_before: None,
+            comment_after: None,
+            variations: vec![],
+        }
+    }
+}
+
+impl PgnGame {
+    pub fn new(start: ChessBoardState) -> Self {
+        PgnGame {
+            tags: vec![],
+            start,
+            moves: vec![],
+            result: "*".to_string(),
+        }
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(k, _)| k == name) {
+            Some(x) => x.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    // appends move to the main line, move must be legal
+    pub fn push_move(&mut self, mv: ChessMove) {
+        let board = self.get_final_board().get_new_pos_after_move(mv);
+        self.moves.push(PgnMove::new(mv, board));
+    }
+
+    pub fn get_final_board(&self) -> ChessBoardState {
+        match self.moves.last() {
+            Some(x) => x.board,
+            None => self.start,
+        }
+    }
+
+    pub fn from_pgn(text: &str) -> Result<Self, PgnError> {
+        let mut games = read_games(text)?;
+        if games.is_empty() {
+            return Err(PgnError {
+                line: 1,
+                column: 1,
+                message: "No game found".to_string(),
+            });
+        }
+        Ok(games.remove(0))
+    }
+
+    // export format
+    pub fn to_pgn(&self) -> String {
+        let mut res = String::new();
+        for name in SEVEN_TAG_ROSTER {
+            let value = match (name, self.get_tag(name)) {
+                ("Result", _) => self.result.as_str(),
+                (_, Some(x)) => x,
+                ("Date", None) => "????.??.??",
+                _ => "?",
+            };
+            res += &Self::get_tag_string(name, value);
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                res += &Self::get_tag_string(name, value);
+            }
+        }
+        if self.get_tag("FEN").is_none() && self.start.to_fen() != START_POS_FEN {
+            res += &Self::get_tag_string("SetUp", "1");
+            res += &Self::get_tag_string("FEN", &self.start.to_fen());
+        }
+        res.push('\n');
+
+        let mut words = vec![];
+        Self::add_line_words(&self.start, &self.moves, &mut words);
+        words.push(self.result.clone());
+
+        let mut line = String::new();
+        for word in words {
+            if !line.is_empty() && line.len() + 1 + word.len() > MAX_LINE_LEN {
+                res += &line;
+                res.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &word;
+        }
+        res += &line;
+        res.push('\n');
+        res
+    }
+
+    fn get_tag_string(name: &str, value: &str) -> String {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("[{} \"{}\"]\n", name, value)
+    }
+
+    fn add_comment_words(comment: &str, words: &mut Vec<String>) {
+        // there is no escape for '}' inside a comment, so it is left out
+        let mut parts: Vec<String> = comment
+            .split_whitespace()
+            .map(|x| x.replace('}', ""))
+            .filter(|x| !x.is_empty())
+            .collect();
+        if parts.is_empty() {
+            parts.push(String::new());
+        }
+        parts[0].insert(0, '{');
+        parts.last_mut().unwrap().push('}');
+        words.append(&mut parts);
+    }
+
+    fn add_line_words(start: &ChessBoardState, moves: &[PgnMove], words: &mut Vec<String>) {
+        let mut board = *start;
+        let mut need_number = true;
+        for mv in moves {
+            if let Some(x) = &mv.comment_before {
+                Self::add_comment_words(x, words);
+                need_number = true;
+            }
+            if board.turn == Color::White {
+                words.push(format!("{}.", board.move_num));
+            } else if need_number {
+                words.push(format!("{}...", board.move_num));
+            }
+            need_number = false;
+
+            words.push(board.get_san_string(mv.mv));
+            for nag in &mv.nags {
+                words.push(format!("${}", nag));
+            }
+            if let Some(x) = &mv.comment_after {
+                Self::add_comment_words(x, words);
+                need_number = true;
+            }
+            for variation in &mv.variations {
+                words.push("(".to_string());
+                Self::add_line_words(&board, &variation.moves, words);
+                if let Some(x) = &variation.comment {
+                    Self::add_comment_words(x, words);
+                }
+                words.push(")".to_string());
+                need_number = true;
+            }
+            board = mv.board;
+        }
+    }
+}
+
+// reads all games from a PGN file
+pub fn read_games(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let tokens = Lexer::new(text).get_tokens()?;
+    let mut parser = Parser { tokens, idx: 0 };
+    parser.parse_games()
+}
+
+pub fn write_games(games: &[PgnGame]) -> String {
+    games
+        .iter()
+        .map(|x| x.to_pgn())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        Lexer {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, line: usize, column: usize, message: &str) -> PgnError {
+        PgnError {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    fn get_tokens(&mut self) -> Result<Vec<(Token, usize, usize)>, PgnError> {
+        let mut res = vec![];
+        loop {
+            let (line, column) = (self.line, self.column);
+            let c = match self.chars.peek() {
+                Some(x) => *x,
+                None => break,
+            };
+            let token = match c {
+                _ if c.is_whitespace() => {
+                    self.next_char();
+                    continue;
+                }
+                // escape mechanism, whole line is ignored
+                '%' if column == 1 => {
+                    self.skip_line();
+                    continue;
+                }
+                ';' => {
+                    self.skip_line();
+                    continue;
+                }
+                '[' => self.read_tag_pair(line, column)?,
+                '{' => {
+                    self.next_char();
+                    let mut comment = String::new();
+                    loop {
+                        match self.next_char() {
+                            Some('}') => break,
+                            Some(x) => comment.push(x),
+                            None => return Err(self.error(line, column, "Unterminated comment")),
+                        }
+                    }
+                    Token::Comment(comment.trim().to_string())
+                }
+                '(' => {
+                    self.next_char();
+                    Token::VariationStart
+                }
+                ')' => {
+                    self.next_char();
+                    Token::VariationEnd
+                }
+                '$' => {
+                    self.next_char();
+                    let digits = self.read_while(|x| x.is_ascii_digit());
+                    match digits.parse::<u8>() {
+                        Ok(x) => Token::Nag(x),
+                        Err(_) => return Err(self.error(line, column, "Wrong NAG")),
+                    }
+                }
+                '!' | '?' => {
+                    let suffix = self.read_while(|x| x == '!' || x == '?');
+                    match Self::get_suffix_nag(&suffix) {
+                        Some(x) => Token::Nag(x),
+                        None => return Err(self.error(line, column, "Wrong move annotation")),
+                    }
+                }
+                '*' => {
+                    self.next_char();
+                    Token::GameResult("*".to_string())
+                }
+                _ if c.is_ascii_alphanumeric() => {
+                    let symbol =
+                        self.read_while(|x| x.is_ascii_alphanumeric() || "_+#=:-/".contains(x));
+                    if ["1-0", "0-1", "1/2-1/2"].contains(&symbol.as_str()) {
+                        Token::GameResult(symbol)
+                    } else if symbol.bytes().all(|x| x.is_ascii_digit()) {
+                        self.read_while(|x| x == '.');
+                        Token::MoveNumber
+                    } else {
+                        Token::San(symbol)
+                    }
+                }
+                '.' => {
+                    self.next_char();
+                    continue;
+                }
+                _ => {
+                    return Err(self.error(line, column, &format!("Unexpected character '{}'", c)))
+                }
+            };
+            res.push((token, line, column));
+        }
+        Ok(res)
+    }
+
+    fn read_while<F: Fn(char) -> bool>(&mut self, f: F) -> String {
+        let mut res = String::new();
+        while let Some(x) = self.chars.peek() {
+            if !f(*x) {
+                break;
+            }
+            res.push(self.next_char().unwrap());
+        }
+        res
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(x) = self.next_char() {
+            if x == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn read_tag_pair(&mut self, line: usize, column: usize) -> Result<Token, PgnError> {
+        self.next_char();
+        self.read_while(|x| x.is_whitespace());
+        let name = self.read_while(|x| x.is_ascii_alphanumeric() || x == '_');
+        if name.is_empty() {
+            return Err(self.error(self.line, self.column, "Expected tag name"));
+        }
+        self.read_while(|x| x.is_whitespace());
+        if self.next_char() != Some('"') {
+            return Err(self.error(self.line, self.column, "Expected tag value"));
+        }
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                Some('\\') => match self.next_char() {
+                    Some(x) => value.push(x),
+                    None => break,
+                },
+                Some('"') => break,
+                Some('\n') | None => {
+                    return Err(self.error(line, column, "Unterminated tag value"))
+                }
+                Some(x) => value.push(x),
+            }
+        }
+        self.read_while(|x| x.is_whitespace());
+        if self.next_char() != Some(']') {
+            return Err(self.error(self.line, self.column, "Expected ']'"));
+        }
+        Ok(Token::TagPair(name, value))
+    }
+
+    fn get_suffix_nag(suffix: &str) -> Option<u8> {
+        match suffix {
+            "!" => Some(1),
+            "?" => Some(2),
+            "!!" => Some(3),
+            "??" => Some(4),
+            "!?" => Some(5),
+            "?!" => Some(6),
+            _ => None,
+        }
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx).map(|x| &x.0)
+    }
+
+    fn error(&self, message: &str) -> PgnError {
+        let (line, column) = match self.tokens.get(self.idx).or(self.tokens.last()) {
+            Some(x) => (x.1, x.2),
+            None => (1, 1),
+        };
+        PgnError {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    fn parse_games(&mut self) -> Result<Vec<PgnGame>, PgnError> {
+        let mut res = vec![];
+        while self.peek().is_some() {
+            res.push(self.parse_game()?);
+        }
+        Ok(res)
+    }
+
+    fn parse_game(&mut self) -> Result<PgnGame, PgnError> {
+        let mut tags = vec![];
+        while let Some(Token::TagPair(name, value)) = self.peek() {
+            tags.push((name.clone(), value.clone()));
+            self.idx += 1;
+        }
+
+        let start = match tags.iter().find(|(k, _)| k == "FEN") {
+            Some((_, fen)) => match ChessBoardState::try_from_fen(fen) {
+                Ok(x) => x,
+                Err(e) => return Err(self.error(&format!("Wrong FEN tag: {}", e))),
+            },
+            None => ChessBoardState::from_fen(START_POS_FEN).unwrap(),
+        };
+        let mut game = PgnGame::new(start);
+        game.tags = tags;
+        if let Some(x) = game.get_tag("Result") {
+            game.result = x.to_string();
+        }
+
+        game.moves = self.parse_line(&start)?.moves;
+        match self.peek() {
+            Some(Token::GameResult(x)) => {
+                game.result = x.clone();
+                self.idx += 1;
+            }
+            Some(Token::VariationEnd) => return Err(self.error("Unexpected ')'")),
+            // next game starts without termination marker, or end of file
+            _ => {}
+        }
+        Ok(game)
+    }
+
+    fn parse_line(&mut self, start: &ChessBoardState) -> Result<PgnVariation, PgnError> {
+        let mut moves: Vec<PgnMove> = vec![];
+        let mut comment_before: Option<String> = None;
+        let mut board = *start;
+        loop {
+            let token = match self.peek() {
+                None
+                | Some(Token::TagPair(..))
+                | Some(Token::GameResult(_))
+                | Some(Token::VariationEnd) => break,
+                Some(x) => x.clone(),
+            };
+            match token {
+                Token::MoveNumber => {}
+                Token::Comment(x) => match moves.last_mut() {
+                    Some(last) if comment_before.is_none() => {
+                        last.comment_after = Some(match &last.comment_after {
+                            Some(prev) => prev.clone() + " " + &x,
+                            None => x,
+                        });
+                    }
+                    _ => {
+                        comment_before = Some(match comment_before {
+                            Some(prev) => prev + " " + &x,
+                            None => x,
+                        });
+                    }
+                },
+                Token::Nag(x) => match moves.last_mut() {
+                    Some(last) => last.nags.push(x),
+                    None => return Err(self.error("NAG before any move")),
+                },
+                Token::VariationStart => {
+                    let before = match moves.len() {
+                        0 => return Err(self.error("Variation before any move")),
+                        1 => *start,
+                        n => moves[n - 2].board,
+                    };
+                    self.idx += 1;
+                    let variation = self.parse_line(&before)?;
+                    if self.peek() != Some(&Token::VariationEnd) {
+                        return Err(self.error("Unterminated variation"));
+                    }
+                    moves.last_mut().unwrap().variations.push(variation);
+                }
+                Token::San(x) => {
+                    let mv = match board.get_chess_move_from_san(&x) {
+                        Some(mv) => mv,
+                        None => return Err(self.error(&format!("Illegal or ambiguous move {}", x))),
+                    };
+                    board = board.get_new_pos_after_move(mv);
+                    let mut pgn_move = PgnMove::new(mv, board);
+                    pgn_move.comment_before = comment_before.take();
+                    moves.push(pgn_move);
+                }
+                _ => unreachable!("Logic err"),
+            }
+            self.idx += 1;
+        }
+        // comment is left over only when there are no moves to hold it
+        Ok(PgnVariation {
+            moves,
+            comment: comment_before,
+        })
+    }
+}
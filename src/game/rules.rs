@@ -1,4 +1,5 @@
 // TODO REMOVE
+use super::bitboard::*;
 use super::board::*;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -22,7 +23,27 @@ pub struct MoveResult {
     pub remove: ChessPiece,
 }
 
+// everything make_move changes and unmake_move can not restore from the move itself
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct UndoRecord {
+    pub mv: ChessMove,
+    pub result: MoveResult,
+    pub castle_state_flags: u8,
+    pub en_passant: PosCode,
+    pub halfmoves_to_draw: u8,
+    pub hash: u64,
+}
+
+// everything make_null_move changes
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NullMoveUndo {
+    pub en_passant: PosCode,
+    pub halfmoves_to_draw: u8,
+    pub hash: u64,
+}
+
 impl ChessMove {
+    #[allow(clippy::needless_return)]
     pub fn get_move_string(&self) -> String {
         return match self.move_type {
             ChessMoveType::Simple => self.mv.get_str(),
@@ -32,6 +53,29 @@ impl ChessMove {
             ChessMoveType::Promotion(x) => self.mv.get_str() + &(x.get_u8() as char).to_string(),
         };
     }
+
+    // long algebraic notation used by UCI: e2e4, e7e8q, e1g1
+    pub fn get_uci_string(&self) -> String {
+        let mut res = self.mv.from.get_str() + &self.mv.to.get_str();
+        if let ChessMoveType::Promotion(x) = self.move_type {
+            res.push(x.get_u8().to_ascii_lowercase() as char);
+        }
+        res
+    }
+
+    // same move in the position from ChessBoardState::mirror
+    pub fn get_mirrored(&self) -> ChessMove {
+        ChessMove {
+            mv: Move {
+                from: self.mv.from.get_mirrored(),
+                to: self.mv.to.get_mirrored(),
+            },
+            move_type: match self.move_type {
+                ChessMoveType::Promotion(x) => ChessMoveType::Promotion(x.get_opposite()),
+                x => x,
+            },
+        }
+    }
 }
 
 impl MoveResult {
@@ -45,23 +89,102 @@ impl MoveResult {
 
 impl ChessBoardState {
     // Apply moves
+    #[allow(clippy::needless_return)]
     pub fn get_new_pos_after_move(&self, mv: ChessMove) -> ChessBoardState {
         let mut new_board = *self;
         new_board.apply_move_force(mv);
         return new_board;
     }
+    #[allow(clippy::needless_return)]
     pub fn get_new_pos_after_move_for_eval(&self, mv: ChessMove) -> (ChessBoardState, MoveResult) {
         let mut new_board = *self;
         let res = new_board.apply_move_force(mv);
         return (new_board, res);
     }
 
+    // in place version of get_new_pos_after_move, returned record must be passed
+    // to unmake_move before any other move is unmade
+    pub fn make_move(&mut self, mv: ChessMove) -> UndoRecord {
+        let castle_state_flags = self.castle_state_flags;
+        let en_passant = self.en_passant;
+        let halfmoves_to_draw = self.halfmoves_to_draw;
+        let hash = self.hash;
+        let result = self.apply_move_force(mv);
+        UndoRecord {
+            mv,
+            result,
+            castle_state_flags,
+            en_passant,
+            halfmoves_to_draw,
+            hash,
+        }
+    }
+
+    pub fn unmake_move(&mut self, undo: &UndoRecord) {
+        let mv = undo.mv.mv;
+        self.turn = self.turn.get_opposite();
+        if self.turn == Color::Black {
+            self.move_num -= 1;
+        }
+        let piece = match undo.mv.move_type {
+            ChessMoveType::Promotion(_) if self.turn == Color::White => ChessPiece::PawnWhite,
+            ChessMoveType::Promotion(_) => ChessPiece::PawnBlack,
+            _ => self.get_piece_unsafe(mv.to),
+        };
+        self.set_piece_unsafe(mv.from, piece);
+        match undo.mv.move_type {
+            ChessMoveType::EnPassant => {
+                self.set_piece_unsafe(mv.to, ChessPiece::None);
+                self.set_piece_unsafe(
+                    Pos {
+                        x: mv.to.x,
+                        y: mv.from.y,
+                    },
+                    undo.result.remove,
+                );
+            }
+            ChessMoveType::CastleLong | ChessMoveType::CastleShort => {
+                self.set_piece_unsafe(mv.to, ChessPiece::None);
+                let (rook_from, rook_to) = Self::get_castle_rook_move(self.turn, undo.mv.move_type);
+                self.set_piece_unsafe(rook_from, self.get_piece_unsafe(rook_to));
+                self.set_piece_unsafe(rook_to, ChessPiece::None);
+            }
+            _ => self.set_piece_unsafe(mv.to, undo.result.remove),
+        }
+        self.castle_state_flags = undo.castle_state_flags;
+        self.en_passant = undo.en_passant;
+        self.halfmoves_to_draw = undo.halfmoves_to_draw;
+        self.hash = undo.hash;
+    }
+
+    // passes the turn without moving, only for search. Position after it is not
+    // reachable in a game, so repetitions across it are not counted
+    pub fn make_null_move(&mut self) -> NullMoveUndo {
+        let undo = NullMoveUndo {
+            en_passant: self.en_passant,
+            halfmoves_to_draw: self.halfmoves_to_draw,
+            hash: self.hash,
+        };
+        self.turn = self.turn.get_opposite();
+        self.hash ^= Self::get_turn_key() ^ Self::get_en_passant_key(self.en_passant);
+        self.en_passant = 0xFF;
+        self.halfmoves_to_draw = 0;
+        undo
+    }
+
+    pub fn unmake_null_move(&mut self, undo: &NullMoveUndo) {
+        self.turn = self.turn.get_opposite();
+        self.en_passant = undo.en_passant;
+        self.halfmoves_to_draw = undo.halfmoves_to_draw;
+        self.hash = undo.hash;
+    }
+
     // Apply moves utils
     fn count_move(&mut self, pawn_move: bool, capture: bool) {
         self.halfmoves_to_draw = if pawn_move || capture {
             0
         } else {
-            self.halfmoves_to_draw + 1
+            self.halfmoves_to_draw.saturating_add(1)
         };
 
         self.move_num += if self.turn == Color::Black { 1 } else { 0 };
@@ -70,23 +193,30 @@ impl ChessBoardState {
         } else {
             Color::White
         };
+        self.hash ^= Self::get_turn_key() ^ Self::get_en_passant_key(self.en_passant);
         self.en_passant = 0xFF;
     }
 
     fn update_castle_flags(&mut self, mv: Move) {
+        let old_flags = self.castle_state_flags;
         // because once moved from here, it means that king or rook move at least once
-        if mv.from.y == 0 && (mv.from.x == 0 || mv.from.x == 4) {
-            self.castle_state_flags &= !(CastleStateFlag::WhiteLong as u8);
-        }
-        if mv.from.y == 0 && (mv.from.x == 7 || mv.from.x == 4) {
-            self.castle_state_flags &= !(CastleStateFlag::WhiteShort as u8);
-        }
-        if mv.from.y == 7 && (mv.from.x == 0 || mv.from.x == 4) {
-            self.castle_state_flags &= !(CastleStateFlag::BlackLong as u8);
-        }
-        if mv.from.y == 7 && (mv.from.x == 7 || mv.from.x == 4) {
-            self.castle_state_flags &= !(CastleStateFlag::BlackShort as u8);
+        // and once moved to a rook square the rook is captured
+        for pos in [mv.from, mv.to] {
+            if pos.y == 0 && (pos.x == 0 || pos.x == 4) {
+                self.castle_state_flags &= !(CastleStateFlag::WhiteLong as u8);
+            }
+            if pos.y == 0 && (pos.x == 7 || pos.x == 4) {
+                self.castle_state_flags &= !(CastleStateFlag::WhiteShort as u8);
+            }
+            if pos.y == 7 && (pos.x == 0 || pos.x == 4) {
+                self.castle_state_flags &= !(CastleStateFlag::BlackLong as u8);
+            }
+            if pos.y == 7 && (pos.x == 7 || pos.x == 4) {
+                self.castle_state_flags &= !(CastleStateFlag::BlackShort as u8);
+            }
         }
+        self.hash ^=
+            Self::get_castling_key(old_flags) ^ Self::get_castling_key(self.castle_state_flags);
     }
 
     // dont check if move follow the rules and if in board
@@ -105,6 +235,12 @@ impl ChessBoardState {
         if pawn_move && mv.from.y == 6 && mv.to.y == 4 {
             self.en_passant = Pos { x: mv.from.x, y: 5 }.get_code();
         }
+        // square that can not be taken on is dropped, so the position is the same
+        // as one reached without the double push
+        if self.en_passant != 0xFF && !self.has_legal_en_passant() {
+            self.en_passant = 0xFF;
+        }
+        self.hash ^= Self::get_en_passant_key(self.en_passant);
         self.update_castle_flags(mv);
         MoveResult::capture(captured)
     }
@@ -126,37 +262,36 @@ impl ChessBoardState {
     fn apply_castle(&mut self, mv: Move, move_type: ChessMoveType) -> MoveResult {
         let color = self.get_piece_unsafe(mv.from).get_color().unwrap();
         self.make_simple_move_force(mv);
-        let to;
-        let from;
+        let (from, to) = Self::get_castle_rook_move(color, move_type);
+        self.set_piece_unsafe(to, self.get_piece_unsafe(from));
+        self.set_piece_unsafe(from, ChessPiece::None);
+        MoveResult {
+            new: ChessPiece::None,
+            remove: ChessPiece::None,
+        }
+    }
+
+    fn get_castle_rook_move(color: Color, move_type: ChessMoveType) -> (Pos, Pos) {
         match (color, move_type) {
             (Color::White, ChessMoveType::CastleShort) => {
-                from = Pos::from_coords(7, 0);
-                to = Pos::from_coords(5, 0);
+                (Pos::from_coords(7, 0), Pos::from_coords(5, 0))
             }
             (Color::White, ChessMoveType::CastleLong) => {
-                from = Pos::from_coords(0, 0);
-                to = Pos::from_coords(3, 0);
+                (Pos::from_coords(0, 0), Pos::from_coords(3, 0))
             }
             (Color::Black, ChessMoveType::CastleShort) => {
-                from = Pos::from_coords(7, 7);
-                to = Pos::from_coords(5, 7);
+                (Pos::from_coords(7, 7), Pos::from_coords(5, 7))
             }
             (Color::Black, ChessMoveType::CastleLong) => {
-                from = Pos::from_coords(0, 7);
-                to = Pos::from_coords(3, 7);
+                (Pos::from_coords(0, 7), Pos::from_coords(3, 7))
             }
             _ => {
                 unreachable!("Logic err");
             }
         }
-        self.set_piece_unsafe(to, self.get_piece_unsafe(from));
-        self.set_piece_unsafe(from, ChessPiece::None);
-        return MoveResult {
-            new: ChessPiece::None,
-            remove: ChessPiece::None,
-        };
     }
 
+    #[allow(clippy::needless_return)]
     fn apply_promotion(&mut self, mv: Move, promotion: ChessPiece) -> MoveResult {
         let res = self.make_simple_move_force(mv);
         self.set_piece_unsafe(mv.to, promotion);
@@ -180,44 +315,23 @@ impl ChessBoardState {
     // move check
 
     // does not exclude moves that leave king open
+    #[allow(clippy::needless_return)]
     pub fn get_all_moves(&self) -> Vec<ChessMove> {
         let mut result = Vec::with_capacity(30);
 
-        for x in 0..BOARD_SIZE {
-            for y in 0..BOARD_SIZE {
-                let pos = Pos {
-                    x: x as u8,
-                    y: y as u8,
-                };
-                let color = self.get_piece_unsafe(pos).get_color();
-                if color.is_some() && color.unwrap() == self.turn {
-                    self.add_all_moves_from_pos(pos, &mut result);
-                }
-            }
+        let mut own = self.get_color_bb(self.turn);
+        while own != 0 {
+            self.add_all_moves_from_pos(square_pos(pop_lsb(&mut own)), &mut result);
         }
 
         return result;
     }
 
     pub fn get_all_moves_checked(&self) -> Vec<ChessMove> {
-        let mut result = vec![];
-
-        for x in 0..BOARD_SIZE {
-            for y in 0..BOARD_SIZE {
-                let pos = Pos {
-                    x: x as u8,
-                    y: y as u8,
-                };
-                let color = self.get_piece_unsafe(pos).get_color();
-                if color.is_some() && color.unwrap() == self.turn {
-                    result.append(&mut self.get_all_moves_from_pos_filtered(pos));
-                }
-            }
-        }
-
-        return result;
+        self.get_legal_moves().iter().map(|x| x.unpack()).collect()
     }
 
+    #[allow(clippy::needless_return)]
     pub fn get_all_moves_from_pos_filtered(&self, from: Pos) -> Vec<ChessMove> {
         let result = self.get_all_moves_from_pos(from);
 
@@ -227,16 +341,19 @@ impl ChessBoardState {
             .collect();
     }
 
+    #[allow(clippy::needless_return)]
     pub fn is_move_allowed_by_rules(&self, mv: ChessMove) -> bool {
         return self.get_new_pos_after_move(mv).get_king_attacked(self.turn);
     }
 
+    #[allow(clippy::needless_return)]
     pub fn get_king_attacked(&self, color: Color) -> bool {
         let pos = self.get_king_pos(color);
         return self.get_pos_attacked(pos, color);
     }
 
     // when cheking if smth is attacked color always means who is attacked
+    #[allow(clippy::needless_return)]
     pub fn check_attacked_direction(
         &self,
         from: Pos,
@@ -265,81 +382,53 @@ impl ChessBoardState {
     }
 
     pub fn get_pos_attacked(&self, from: Pos, color: Color) -> bool {
-        let rook_dir = if color == Color::White {
-            [ChessPiece::RookBlack, ChessPiece::QueenBlack]
-        } else {
-            [ChessPiece::RookWhite, ChessPiece::QueenWhite]
-        };
-        let bishop_dir = if color == Color::White {
-            [ChessPiece::BishopBlack, ChessPiece::QueenBlack]
-        } else {
-            [ChessPiece::BishopWhite, ChessPiece::QueenWhite]
-        };
-        let pawn = if color == Color::White {
-            [ChessPiece::PawnBlack]
-        } else {
-            [ChessPiece::PawnWhite]
-        };
-        let knight = if color == Color::White {
-            [ChessPiece::KnightBlack]
-        } else {
-            [ChessPiece::KnightWhite]
-        };
-        let king = if color == Color::White {
-            [ChessPiece::KingBlack]
+        if !Self::pos_in_bounds(from) {
+            return false;
+        }
+        let idx = Self::get_pos_idx(from);
+        let occupied = self.get_occupied_bb();
+        let (pawn, knight, bishop, rook, queen, king) = if color == Color::White {
+            (
+                ChessPiece::PawnBlack,
+                ChessPiece::KnightBlack,
+                ChessPiece::BishopBlack,
+                ChessPiece::RookBlack,
+                ChessPiece::QueenBlack,
+                ChessPiece::KingBlack,
+            )
         } else {
-            [ChessPiece::KingWhite]
+            (
+                ChessPiece::PawnWhite,
+                ChessPiece::KnightWhite,
+                ChessPiece::BishopWhite,
+                ChessPiece::RookWhite,
+                ChessPiece::QueenWhite,
+                ChessPiece::KingWhite,
+            )
         };
-        return self.check_attacked_direction(from, 0, 1, 8, &rook_dir)
-            || self.check_attacked_direction(from, 0, -1, 8, &rook_dir)
-            || self.check_attacked_direction(from, -1, 0, 8, &rook_dir)
-            || self.check_attacked_direction(from, 1, 0, 8, &rook_dir)
-            ////
-            || self.check_attacked_direction(from, 1, 1, 8, &bishop_dir)
-            || self.check_attacked_direction(from, 1, -1, 8, &bishop_dir)
-            || self.check_attacked_direction(from, -1, 1, 8, &bishop_dir)
-            || self.check_attacked_direction(from, 1, 1, 8, &bishop_dir)
-            ////
-            || self.check_attacked_direction(from, 1, 0, 1, &king)
-            || self.check_attacked_direction(from, -1, 0, 1, &king)
-            || self.check_attacked_direction(from, 0, 1, 1, &king)
-            || self.check_attacked_direction(from, 0, -1, 1, &king)
-            || self.check_attacked_direction(from, 1, 1, 1, &king)
-            || self.check_attacked_direction(from, 1, -1, 1, &king)
-            || self.check_attacked_direction(from, -1, 1, 1, &king)
-            || self.check_attacked_direction(from, -1, -1, 1, &king)
-            //// pawns
-            || if color == Color::White {
-                self.check_attacked_direction(from, 1, 1, 1, &pawn) ||
-                self.check_attacked_direction(from, -1, 1, 1, &pawn)
-            } else {
-                self.check_attacked_direction(from, 1, -1, 1, &pawn) ||
-                self.check_attacked_direction(from, -1, -1, 1, &pawn)
-            }
-            //// knights
-            || self.check_attacked_direction(from, 1, 2, 1, &knight)
-            || self.check_attacked_direction(from, -1, 2, 1, &knight)
-            || self.check_attacked_direction(from, 1, -2, 1, &knight)
-            || self.check_attacked_direction(from, -1, -2, 1, &knight)
-            || self.check_attacked_direction(from, 2, 1, 1, &knight)
-            || self.check_attacked_direction(from, 2, -1, 1, &knight)
-            || self.check_attacked_direction(from, -2, 1, 1, &knight)
-            || self.check_attacked_direction(from, -2, -1, 1, &knight);
+        let queens = self.get_piece_bb(queen);
+        // pawn of attacked color would attack the same squares the enemy pawns attack it from
+        pawn_attacks(color, idx) & self.get_piece_bb(pawn) != 0
+            || knight_attacks(idx) & self.get_piece_bb(knight) != 0
+            || king_attacks(idx) & self.get_piece_bb(king) != 0
+            || bishop_attacks(idx, occupied) & (self.get_piece_bb(bishop) | queens) != 0
+            || rook_attacks(idx, occupied) & (self.get_piece_bb(rook) | queens) != 0
     }
 
     pub fn get_king_pos(&self, color: Color) -> Pos {
-        for x in 0..BOARD_SIZE {
-            for y in 0..BOARD_SIZE {
-                if color == Color::White
-                    && self.get_piece_coords_unsafe(x, y) == ChessPiece::KingWhite
-                {
-                    return Pos::from_coords(x as i8, y as i8);
-                }
-            }
+        let king = if color == Color::White {
+            ChessPiece::KingWhite
+        } else {
+            ChessPiece::KingBlack
+        };
+        let bb = self.get_piece_bb(king);
+        if bb == 0 {
+            return Pos::from_code(0xFF);
         }
-        return Pos::from_code(0xFF);
+        square_pos(bb.trailing_zeros() as usize)
     }
 
+    #[allow(clippy::needless_return)]
     pub fn is_legal_move(&self, mv: ChessMove) -> bool {
         let piece_opt = self.get_piece(mv.mv.from);
         if piece_opt.is_none() {
@@ -355,6 +444,7 @@ impl ChessBoardState {
 
     // get moves
 
+    #[allow(clippy::needless_return)]
     pub fn get_all_moves_from_pos(&self, from: Pos) -> Vec<ChessMove> {
         let mut res = vec![];
 
@@ -364,52 +454,43 @@ impl ChessBoardState {
     }
 
     pub fn add_all_moves_from_pos(&self, from: Pos, res: &mut Vec<ChessMove>) {
-
-        match self.get_piece_unsafe(from) {
-            ChessPiece::None => {}
-            ChessPiece::RookBlack | ChessPiece::RookWhite => self.add_rook_moves(from, res),
-            ChessPiece::KnightBlack | ChessPiece::KnightWhite => {
-                self.add_knight_moves(from, res)
-            }
-            ChessPiece::BishopBlack | ChessPiece::BishopWhite => {
-                self.add_bishop_moves(from, res)
+        let piece = self.get_piece_unsafe(from);
+        let color = match piece.get_color() {
+            Some(x) => x,
+            None => return,
+        };
+        let idx = Self::get_pos_idx(from);
+        let occupied = self.get_occupied_bb();
+        let targets = match piece {
+            ChessPiece::RookBlack | ChessPiece::RookWhite => rook_attacks(idx, occupied),
+            ChessPiece::KnightBlack | ChessPiece::KnightWhite => knight_attacks(idx),
+            ChessPiece::BishopBlack | ChessPiece::BishopWhite => bishop_attacks(idx, occupied),
+            ChessPiece::QueenBlack | ChessPiece::QueenWhite => queen_attacks(idx, occupied),
+            ChessPiece::KingBlack | ChessPiece::KingWhite => {
+                self.add_castle_moves(from, res);
+                king_attacks(idx)
             }
-            ChessPiece::QueenBlack | ChessPiece::QueenWhite => self.add_queen_moves(from, res),
-            ChessPiece::KingBlack | ChessPiece::KingWhite => self.add_king_moves(from, res),
-            ChessPiece::PawnBlack | ChessPiece::PawnWhite => self.add_pawn_moves(from, res),
-        }
+            _ => return self.add_pawn_moves(from, res),
+        };
+        self.add_moves_to(from, targets & !self.get_color_bb(color), res);
     }
 
     // Movegen utils
 
-    fn add_rook_moves(&self, from: Pos, res: &mut Vec<ChessMove>) {
-        self.add_moves_in_direction(from, 1, 0, 8, res);
-        self.add_moves_in_direction(from, 0, 1, 8, res);
-        self.add_moves_in_direction(from, -1, 0, 8, res);
-        self.add_moves_in_direction(from, 0, -1, 8, res);
-    }
-    fn add_knight_moves(&self, from: Pos, res: &mut Vec<ChessMove>) {
-        self.add_moves_in_direction(from, 2, 1, 1, res);
-        self.add_moves_in_direction(from, 1, 2, 1, res);
-        self.add_moves_in_direction(from, -2, 1, 1, res);
-        self.add_moves_in_direction(from, -1, 2, 1, res);
-        self.add_moves_in_direction(from, 2, -1, 1, res);
-        self.add_moves_in_direction(from, 1, -2, 1, res);
-        self.add_moves_in_direction(from, -2, -1, 1, res);
-        self.add_moves_in_direction(from, -1, -2, 1, res);
-    }
-    fn add_bishop_moves(&self, from: Pos, res: &mut Vec<ChessMove>) {
-        self.add_moves_in_direction(from, 1, 1, 8, res);
-        self.add_moves_in_direction(from, -1, -1, 8, res);
-        self.add_moves_in_direction(from, -1, 1, 8, res);
-        self.add_moves_in_direction(from, 1, -1, 8, res);
-    }
-    fn add_queen_moves(&self, from: Pos, res: &mut Vec<ChessMove>) {
-        self.add_bishop_moves(from, res);
-        self.add_rook_moves(from, res);
-    }
-
-    fn check_castle(&self, from: Pos, dir: i8) -> bool {
+    fn add_moves_to(&self, from: Pos, mut targets: Bitboard, res: &mut Vec<ChessMove>) {
+        while targets != 0 {
+            res.push(ChessMove {
+                mv: Move {
+                    from,
+                    to: square_pos(pop_lsb(&mut targets)),
+                },
+                move_type: ChessMoveType::Simple,
+            });
+        }
+    }
+
+    #[allow(clippy::needless_return)]
+    pub(crate) fn check_castle(&self, from: Pos, dir: i8) -> bool {
         if self.get_piece_coords_i8_unsafe(from.x as i8 + dir, from.y as i8) != ChessPiece::None {
             return false;
         }
@@ -423,26 +504,19 @@ impl ChessBoardState {
         {
             return false;
         }
-        if self.get_pos_attacked(
-            Pos::from_coords(from.x as i8 + dir, from.y as i8),
-            self.get_piece_unsafe(from).get_color().unwrap(),
-        ) {
+        // cannot castle out of check or through attacked square,
+        // landing square is checked as for any other king move
+        let color = self.get_piece_unsafe(from).get_color().unwrap();
+        if self.get_pos_attacked(from, color)
+            || self.get_pos_attacked(Pos::from_coords(from.x as i8 + dir, from.y as i8), color)
+        {
             return false;
         }
 
         return true;
     }
 
-    fn add_king_moves(&self, from: Pos, res: &mut Vec<ChessMove>) {
-        self.add_moves_in_direction(from, 1, 1, 1, res);
-        self.add_moves_in_direction(from, -1, 1, 1, res);
-        self.add_moves_in_direction(from, 1, -1, 1, res);
-        self.add_moves_in_direction(from, -1, -1, 1, res);
-        self.add_moves_in_direction(from, 1, 0, 1, res);
-        self.add_moves_in_direction(from, -1, 0, 1, res);
-        self.add_moves_in_direction(from, 0, 1, 1, res);
-        self.add_moves_in_direction(from, 0, -1, 1, res);
-        // TODO castle
+    fn add_castle_moves(&self, from: Pos, res: &mut Vec<ChessMove>) {
         let color: Color = self.get_piece_unsafe(from).get_color().unwrap();
         if color == Color::White
             && (self.castle_state_flags & (CastleStateFlag::WhiteShort as u8) != 0)
@@ -450,7 +524,7 @@ impl ChessBoardState {
         {
             res.push(ChessMove {
                 mv: Move {
-                    from: from,
+                    from,
                     to: Pos {
                         x: from.x + 2,
                         y: from.y,
@@ -465,9 +539,9 @@ impl ChessBoardState {
         {
             res.push(ChessMove {
                 mv: Move {
-                    from: from,
+                    from,
                     to: Pos {
-                        x: from.x + 2,
+                        x: from.x - 2,
                         y: from.y,
                     },
                 },
@@ -480,7 +554,7 @@ impl ChessBoardState {
         {
             res.push(ChessMove {
                 mv: Move {
-                    from: from,
+                    from,
                     to: Pos {
                         x: from.x + 2,
                         y: from.y,
@@ -495,9 +569,9 @@ impl ChessBoardState {
         {
             res.push(ChessMove {
                 mv: Move {
-                    from: from,
+                    from,
                     to: Pos {
-                        x: from.x + 2,
+                        x: from.x - 2,
                         y: from.y,
                     },
                 },
@@ -507,127 +581,73 @@ impl ChessBoardState {
     }
 
     fn add_pawn_moves(&self, from: Pos, res: &mut Vec<ChessMove>) {
-        let mut result = vec![];
-        let piece_color = self.get_piece_unsafe(from).get_color().unwrap();
-        let y_dir = if piece_color == Color::White { 1 } else { -1 };
-
-        let step_num = if piece_color == Color::White && from.y == 1
-            || piece_color == Color::Black && from.y == 6
-        {
-            2
+        let color = self.get_piece_unsafe(from).get_color().unwrap();
+        let idx = Self::get_pos_idx(from);
+        let empty = self.get_piece_bb(ChessPiece::None);
+        let (step, start_y, last_y): (i8, u8, u8) = if color == Color::White {
+            (1, 1, 7)
         } else {
-            1
+            (-1, 6, 0)
         };
 
-        self.add_moves_in_direction_general(from, 0, y_dir, step_num, false, false, &mut result);
-        self.add_moves_in_direction_general(from, 1, y_dir, 1, true, true, &mut result);
-        self.add_moves_in_direction_general(from, -1, y_dir, 1, true, true, &mut result);
-
-        let en_passant = Pos::from_code(self.en_passant);
-
-        if (en_passant.y as i8 - from.y as i8) == y_dir
-            && [-1 as i8, 1 as i8].contains(&(en_passant.x as i8 - from.x as i8))
-        {
-            self.add_moves_in_direction_general(
-                from,
-                en_passant.x as i8 - from.x as i8,
-                y_dir,
-                1,
-                false,
-                false,
-                &mut result,
-            );
-            if result.len() > 0 {
-                result.last_mut().unwrap().move_type = ChessMoveType::EnPassant;
-                // костыль мб както подругому TODO вернуть сколько ходов добавил
+        let mut targets = pawn_attacks(color, idx) & self.get_color_bb(color.get_opposite());
+        let one = Pos::from_coords(from.x as i8, from.y as i8 + step);
+        if Self::pos_in_bounds(one) && square_bb(one) & empty != 0 {
+            targets |= square_bb(one);
+            let two = Pos::from_coords(from.x as i8, from.y as i8 + 2 * step);
+            if from.y == start_y && square_bb(two) & empty != 0 {
+                targets |= square_bb(two);
             }
         }
 
-        if result.len() > 0 && piece_color == Color::White && from.y == 6
-            || result.len() > 0 && piece_color == Color::Black && from.y == 1
-        {
-            let proms = if piece_color == Color::White {
-                [
-                    ChessPiece::RookWhite,
-                    ChessPiece::QueenWhite,
-                    ChessPiece::BishopWhite,
-                    ChessPiece::KnightWhite,
-                ]
-            } else {
-                [
-                    ChessPiece::RookBlack,
-                    ChessPiece::QueenBlack,
-                    ChessPiece::BishopBlack,
-                    ChessPiece::KnightBlack,
-                ]
-            };
-            for mv in result {
-                for prom_piece in proms {
-                    res.push(ChessMove {
-                        mv: mv.mv,
-                        move_type: ChessMoveType::Promotion(prom_piece),
-                    });
-                }
-            }
+        let proms = if color == Color::White {
+            [
+                ChessPiece::RookWhite,
+                ChessPiece::QueenWhite,
+                ChessPiece::BishopWhite,
+                ChessPiece::KnightWhite,
+            ]
         } else {
-            for mv in result {
-                res.push(mv);
+            [
+                ChessPiece::RookBlack,
+                ChessPiece::QueenBlack,
+                ChessPiece::BishopBlack,
+                ChessPiece::KnightBlack,
+            ]
+        };
+        while targets != 0 {
+            let to = square_pos(pop_lsb(&mut targets));
+            if to.y != last_y {
+                res.push(ChessMove {
+                    mv: Move { from, to },
+                    move_type: ChessMoveType::Simple,
+                });
+                continue;
+            }
+            for prom_piece in proms {
+                res.push(ChessMove {
+                    mv: Move { from, to },
+                    move_type: ChessMoveType::Promotion(prom_piece),
+                });
             }
         }
-    }
 
-    fn add_moves_in_direction_general(
-        &self,
-        from: Pos,
-        step_x: i8,
-        step_y: i8,
-        step_num: usize,
-        can_take: bool,
-        must_take: bool,
-        res: &mut Vec<ChessMove>,
-    ) {
-        let color = self.get_piece_unsafe(from).get_color().unwrap();
-        let mut x = from.x as i8 + step_x;
-        let mut y = from.y as i8 + step_y;
-        for _ in 0..step_num {
-            if !Self::coords_in_bounds(x, y) {
-                break;
-            }
-            let to = Pos::from_coords(x, y);
-            let piece_to_color = self.get_piece_unsafe(to).get_color();
-            if piece_to_color.is_some_and(|x| x == color) {
-                break;
-            }
-            if must_take && piece_to_color.is_none() {
-                break;
-            }
-            if !can_take && piece_to_color.is_some() {
-                break;
-            }
-            res.push(ChessMove {
-                mv: Move { from: from, to: to },
-                move_type: ChessMoveType::Simple,
-            });
-            if piece_to_color.is_some() {
-                break;
+        if self.en_passant != 0xFF {
+            let en_passant = Pos::from_code(self.en_passant);
+            if pawn_attacks(color, idx) & square_bb(en_passant) != 0 {
+                res.push(ChessMove {
+                    mv: Move {
+                        from,
+                        to: en_passant,
+                    },
+                    move_type: ChessMoveType::EnPassant,
+                });
             }
-            x += step_x;
-            y += step_y;
         }
     }
 
-    fn add_moves_in_direction(
-        &self,
-        from: Pos,
-        step_x: i8,
-        step_y: i8,
-        step_num: usize,
-        res: &mut Vec<ChessMove>,
-    ) {
-        self.add_moves_in_direction_general(from, step_x, step_y, step_num, true, false, res);
-    }
-
     // Display and input
+    #[allow(clippy::needless_return)]
     pub fn get_move_string(&self, mv: ChessMove) -> String {
         let piece = self.get_piece_unsafe(mv.mv.from);
         return match mv.move_type {
@@ -677,8 +697,8 @@ impl ChessBoardState {
         if move_str.len() < 5 {
             return None;
         }
-        if *move_str.as_bytes().get(0).unwrap() < b'a'
-            || *move_str.as_bytes().get(0).unwrap() > b'h'
+        if *move_str.as_bytes().first().unwrap() < b'a'
+            || *move_str.as_bytes().first().unwrap() > b'h'
             || *move_str.as_bytes().get(3).unwrap() < b'a'
             || *move_str.as_bytes().get(3).unwrap() > b'h'
             || *move_str.as_bytes().get(1).unwrap() < b'1'
@@ -691,6 +711,15 @@ impl ChessBoardState {
         Some(self.get_chess_move_from_string_unsafe(move_str))
     }
 
+    // accepts only legal moves in long algebraic notation (e2e4, e7e8q)
+    pub fn get_chess_move_from_uci(&self, move_str: &str) -> Option<ChessMove> {
+        self
+            .get_all_moves_checked()
+            .into_iter()
+            .find(|mv| mv.get_uci_string() == move_str)
+    }
+
+    #[allow(clippy::needless_return, clippy::redundant_field_names)]
     fn get_chess_move_from_string_unsafe(&self, move_str: &str) -> ChessMove {
         let mv = Move::from_str(move_str);
         let piece = self.get_piece_unsafe(mv.from);
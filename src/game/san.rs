@@ -0,0 +1,164 @@
+use super::board::*;
+use super::rules::*;
+
+// Standard Algebraic Notation: Nf3, exd5, O-O, e8=Q+, Raxd1#
+impl ChessBoardState {
+    pub fn get_san_string(&self, mv: ChessMove) -> String {
+        let mut res = match mv.move_type {
+            ChessMoveType::CastleLong => "O-O-O".to_string(),
+            ChessMoveType::CastleShort => "O-O".to_string(),
+            _ => self.get_san_string_no_suffix(mv),
+        };
+
+        let new_board = self.get_new_pos_after_move(mv);
+        if new_board.get_king_attacked(new_board.turn) {
+            if new_board.get_all_moves_checked().is_empty() {
+                res.push('#');
+            } else {
+                res.push('+');
+            }
+        }
+        res
+    }
+
+    // accepts lenient variants too: 0-0, e8Q, Ng1-f3, e2e4, Nbd7!?
+    pub fn get_chess_move_from_san(&self, san_str: &str) -> Option<ChessMove> {
+        let san = san_str.trim_end_matches(['+', '#', '!', '?']);
+        let legal_moves = self.get_all_moves_checked();
+
+        if san == "O-O" || san == "0-0" {
+            return legal_moves
+                .into_iter()
+                .find(|mv| mv.move_type == ChessMoveType::CastleShort);
+        }
+        if san == "O-O-O" || san == "0-0-0" {
+            return legal_moves
+                .into_iter()
+                .find(|mv| mv.move_type == ChessMoveType::CastleLong);
+        }
+
+        let mut bytes: Vec<u8> = san.bytes().filter(|x| *x != b'-' && *x != b'=').collect();
+
+        let piece = match bytes.first() {
+            Some(x @ (b'K' | b'Q' | b'R' | b'B' | b'N')) => {
+                let x = *x;
+                bytes.remove(0);
+                x
+            }
+            _ => b'P',
+        };
+
+        let promotion = match bytes.last() {
+            Some(x) if piece == b'P' && b"QRBNqrbn".contains(x) => {
+                let x = x.to_ascii_uppercase();
+                bytes.pop();
+                Some(x)
+            }
+            _ => None,
+        };
+
+        if bytes.len() < 2 {
+            return None;
+        }
+        let to = Self::parse_san_square(&bytes[bytes.len() - 2..])?;
+        // what is left is disambiguation and capture sign
+        let mut from_x = None;
+        let mut from_y = None;
+        for c in &bytes[..bytes.len() - 2] {
+            match c {
+                b'a'..=b'h' => from_x = Some(c - b'a'),
+                b'1'..=b'8' => from_y = Some(c - b'1'),
+                b'x' | b':' => {}
+                _ => return None,
+            }
+        }
+
+        let mut candidates = legal_moves.into_iter().filter(|mv| {
+            let move_piece = self
+                .get_piece_unsafe(mv.mv.from)
+                .get_u8()
+                .to_ascii_uppercase();
+            let move_promotion = match mv.move_type {
+                ChessMoveType::Promotion(x) => Some(x.get_u8().to_ascii_uppercase()),
+                _ => None,
+            };
+            move_piece == piece
+                && mv.mv.to == to
+                && from_x.is_none_or(|x| x == mv.mv.from.x)
+                && from_y.is_none_or(|y| y == mv.mv.from.y)
+                && move_promotion == promotion
+                && mv.move_type != ChessMoveType::CastleLong
+                && mv.move_type != ChessMoveType::CastleShort
+        });
+
+        let res = candidates.next()?;
+        if candidates.next().is_some() {
+            // ambiguous
+            return None;
+        }
+        Some(res)
+    }
+
+    fn get_san_string_no_suffix(&self, mv: ChessMove) -> String {
+        let piece = self.get_piece_unsafe(mv.mv.from);
+        let piece_char = piece.get_u8().to_ascii_uppercase();
+        let capture = mv.move_type == ChessMoveType::EnPassant
+            || self.get_piece_unsafe(mv.mv.to) != ChessPiece::None;
+        let mut res = String::new();
+
+        if piece_char == b'P' {
+            if capture {
+                res.push((mv.mv.from.x + b'a') as char);
+            }
+        } else {
+            res.push(piece_char as char);
+            res += &self.get_san_disambiguation(mv);
+        }
+        if capture {
+            res.push('x');
+        }
+        res += &mv.mv.to.get_str();
+        if let ChessMoveType::Promotion(x) = mv.move_type {
+            res.push('=');
+            res.push(x.get_u8().to_ascii_uppercase() as char);
+        }
+        res
+    }
+
+    fn get_san_disambiguation(&self, mv: ChessMove) -> String {
+        let piece = self.get_piece_unsafe(mv.mv.from);
+        let others: Vec<Pos> = self
+            .get_all_moves_checked()
+            .into_iter()
+            .filter(|x| {
+                x.mv.to == mv.mv.to
+                    && x.mv.from != mv.mv.from
+                    && self.get_piece_unsafe(x.mv.from) == piece
+            })
+            .map(|x| x.mv.from)
+            .collect();
+
+        if others.is_empty() {
+            return String::new();
+        }
+        let file = ((mv.mv.from.x + b'a') as char).to_string();
+        let rank = ((mv.mv.from.y + b'1') as char).to_string();
+        if others.iter().all(|x| x.x != mv.mv.from.x) {
+            return file;
+        }
+        if others.iter().all(|x| x.y != mv.mv.from.y) {
+            return rank;
+        }
+        file + &rank
+    }
+
+    fn parse_san_square(s: &[u8]) -> Option<Pos> {
+        if !(b'a'..=b'h').contains(&s[0]) || !(b'1'..=b'8').contains(&s[1]) {
+            return None;
+        }
+        Some(Pos {
+            x: s[0] - b'a',
+            y: s[1] - b'1',
+        })
+    }
+}
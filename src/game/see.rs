@@ -0,0 +1,87 @@
+use super::bitboard::*;
+use super::board::*;
+use super::rules::*;
+
+// Values used only to resolve exchanges, king is worth more than any material it could win
+pub const SEE_PAWN_VALUE: i32 = 100;
+const SEE_KING_VALUE: i32 = 20000;
+
+// longest possible exchange, every piece of both sides captures on the square once
+const MAX_EXCHANGE: usize = 32;
+
+impl ChessPiece {
+    pub fn get_see_value(&self) -> i32 {
+        match *self {
+            ChessPiece::None => 0,
+            ChessPiece::PawnWhite | ChessPiece::PawnBlack => SEE_PAWN_VALUE,
+            ChessPiece::KnightWhite | ChessPiece::KnightBlack => 300,
+            ChessPiece::BishopWhite | ChessPiece::BishopBlack => 300,
+            ChessPiece::RookWhite | ChessPiece::RookBlack => 500,
+            ChessPiece::QueenWhite | ChessPiece::QueenBlack => 900,
+            ChessPiece::KingWhite | ChessPiece::KingBlack => SEE_KING_VALUE,
+        }
+    }
+}
+
+impl ChessBoardState {
+    // static exchange evaluation: material won by the side to move if mv starts a sequence of
+    // captures on its target square, both sides recapturing with the least valuable piece and
+    // stopping when it does not pay off. Sliders behind a piece that has captured join in as
+    // the piece leaves its square. Pins are not taken into account
+    pub fn see(&self, mv: ChessMove) -> i32 {
+        let from = Self::get_pos_idx(mv.mv.from);
+        let to = Self::get_pos_idx(mv.mv.to);
+        let mut occupied = self.get_occupied_bb();
+        let mut gain = [0; MAX_EXCHANGE];
+        // piece that stands on the target square and can be taken next
+        let mut piece = self.board[from];
+        gain[0] = self.board[to].get_see_value();
+        match mv.move_type {
+            ChessMoveType::EnPassant => {
+                let captured = Pos::from_coords(mv.mv.to.x as i8, mv.mv.from.y as i8);
+                occupied &= !square_bb(captured);
+                gain[0] = SEE_PAWN_VALUE;
+            }
+            ChessMoveType::Promotion(x) => {
+                gain[0] += x.get_see_value() - SEE_PAWN_VALUE;
+                piece = x;
+            }
+            _ => {}
+        }
+
+        let mut color = self.turn;
+        let mut attacker = 1 << from;
+        let mut depth = 0;
+        while attacker != 0 && depth + 1 < MAX_EXCHANGE {
+            depth += 1;
+            // score if the piece on the square is taken, the other side may decline later
+            gain[depth] = piece.get_see_value() - gain[depth - 1];
+            occupied &= !attacker;
+            color = color.get_opposite();
+            let attackers = self.get_attackers(to, color, occupied);
+            attacker = 0;
+            let mut least = SEE_KING_VALUE + 1;
+            let mut bb = attackers;
+            while bb != 0 {
+                let idx = pop_lsb(&mut bb);
+                let value = self.board[idx].get_see_value();
+                if value < least {
+                    least = value;
+                    attacker = 1 << idx;
+                    piece = self.board[idx];
+                }
+            }
+            // king can take only if the square is not defended anymore
+            if least == SEE_KING_VALUE
+                && self.get_attackers(to, color.get_opposite(), occupied & !attacker) != 0
+            {
+                break;
+            }
+        }
+        while depth > 1 {
+            depth -= 1;
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        }
+        gain[0]
+    }
+}
@@ -0,0 +1,116 @@
+use super::board::*;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GameStatus {
+    Ongoing,
+    // color of the winner
+    Checkmate(Color),
+    Stalemate,
+    InsufficientMaterial,
+    // can be claimed by a player, engine always claims it
+    FiftyMoveRule,
+    // draw without any claim
+    SeventyFiveMoveRule,
+    // repetition rules need game history, see Game::get_status
+    ThreefoldRepetition,
+    FivefoldRepetition,
+}
+
+impl GameResult {
+    pub fn get_str(&self) -> &'static str {
+        match *self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+}
+
+impl GameStatus {
+    pub fn get_result(&self) -> Option<GameResult> {
+        match *self {
+            GameStatus::Ongoing => None,
+            GameStatus::Checkmate(Color::White) => Some(GameResult::WhiteWins),
+            GameStatus::Checkmate(Color::Black) => Some(GameResult::BlackWins),
+            _ => Some(GameResult::Draw),
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::Ongoing
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        match *self {
+            GameStatus::Ongoing => "Game is not over",
+            GameStatus::Checkmate(Color::White) => "White mates",
+            GameStatus::Checkmate(Color::Black) => "Black mates",
+            GameStatus::Stalemate => "Stalemate",
+            GameStatus::InsufficientMaterial => "Insufficient material",
+            GameStatus::FiftyMoveRule => "Fifty move rule",
+            GameStatus::SeventyFiveMoveRule => "Seventy-five move rule",
+            GameStatus::ThreefoldRepetition => "Threefold repetition",
+            GameStatus::FivefoldRepetition => "Fivefold repetition",
+        }
+    }
+}
+
+impl ChessBoardState {
+    pub fn get_game_status(&self) -> GameStatus {
+        self.get_game_status_with_moves(&self.get_all_moves_checked())
+    }
+
+    // same as get_game_status when legal moves are already generated, in any form
+    pub fn get_game_status_with_moves<T>(&self, legal_moves: &[T]) -> GameStatus {
+        // mate on the last move before move rules still counts
+        if legal_moves.is_empty() {
+            if !self.get_king_attacked(self.turn) {
+                return GameStatus::Stalemate;
+            }
+            return GameStatus::Checkmate(if self.turn == Color::White {
+                Color::Black
+            } else {
+                Color::White
+            });
+        }
+        if self.halfmoves_to_draw >= 150 {
+            return GameStatus::SeventyFiveMoveRule;
+        }
+        if self.is_insufficient_material() {
+            return GameStatus::InsufficientMaterial;
+        }
+        if self.halfmoves_to_draw >= 100 {
+            return GameStatus::FiftyMoveRule;
+        }
+        GameStatus::Ongoing
+    }
+
+    // no sequence of legal moves can lead to mate: K vs K, single minor piece,
+    // or only bishops all standing on squares of one color
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut bishops_on_color = [0, 0];
+        for (i, piece) in self.board.iter().enumerate() {
+            match piece {
+                ChessPiece::None | ChessPiece::KingWhite | ChessPiece::KingBlack => {}
+                ChessPiece::KnightWhite | ChessPiece::KnightBlack => knights += 1,
+                ChessPiece::BishopWhite | ChessPiece::BishopBlack => {
+                    bishops_on_color[(i / BOARD_SIZE + i % BOARD_SIZE) % 2] += 1
+                }
+                _ => return false,
+            }
+        }
+        let bishops = bishops_on_color[0] + bishops_on_color[1];
+        if knights + bishops <= 1 {
+            return true;
+        }
+        knights == 0 && (bishops_on_color[0] == 0 || bishops_on_color[1] == 0)
+    }
+}
@@ -0,0 +1,93 @@
+use super::board::*;
+
+// Zobrist hashing: position key is xor of random keys of everything in the position.
+// Keys are generated at compile time, so hashes are the same in every run
+struct ZobristKeys {
+    // indexed by ChessPiece as u8, keys of ChessPiece::None are zero
+    pieces: [[u64; BOARD_ARRAY_SIZE]; 13],
+    black_to_move: u64,
+    // indexed by castle_state_flags
+    castling: [u64; 16],
+    // indexed by file of en passant square
+    en_passant: [u64; BOARD_SIZE],
+}
+
+static KEYS: ZobristKeys = ZobristKeys::new();
+
+// splitmix64, good enough and needs no state except one number
+const fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+impl ZobristKeys {
+    const fn new() -> Self {
+        let mut state = 0x5EED_C0DE_u64;
+        let mut res = ZobristKeys {
+            pieces: [[0; BOARD_ARRAY_SIZE]; 13],
+            black_to_move: 0,
+            castling: [0; 16],
+            en_passant: [0; BOARD_SIZE],
+        };
+        let mut piece = 1;
+        while piece < 13 {
+            let mut i = 0;
+            while i < BOARD_ARRAY_SIZE {
+                res.pieces[piece][i] = next_random(&mut state);
+                i += 1;
+            }
+            piece += 1;
+        }
+        res.black_to_move = next_random(&mut state);
+        let mut i = 0;
+        while i < 16 {
+            res.castling[i] = next_random(&mut state);
+            i += 1;
+        }
+        let mut i = 0;
+        while i < BOARD_SIZE {
+            res.en_passant[i] = next_random(&mut state);
+            i += 1;
+        }
+        res
+    }
+}
+
+impl ChessBoardState {
+    // key of the position computed from scratch, must always be equal to hash field
+    pub fn compute_hash(&self) -> u64 {
+        let mut res = 0;
+        for (i, piece) in self.board.iter().enumerate() {
+            res ^= KEYS.pieces[*piece as usize][i];
+        }
+        if self.turn == Color::Black {
+            res ^= KEYS.black_to_move;
+        }
+        res ^= Self::get_castling_key(self.castle_state_flags);
+        res ^= Self::get_en_passant_key(self.en_passant);
+        res
+    }
+
+    // Keys for incremental update
+    pub(crate) fn get_piece_key(piece: ChessPiece, idx: usize) -> u64 {
+        KEYS.pieces[piece as usize][idx]
+    }
+
+    pub(crate) fn get_turn_key() -> u64 {
+        KEYS.black_to_move
+    }
+
+    pub(crate) fn get_castling_key(castle_state_flags: u8) -> u64 {
+        KEYS.castling[castle_state_flags as usize & 0xF]
+    }
+
+    pub(crate) fn get_en_passant_key(en_passant: PosCode) -> u64 {
+        if en_passant == 0xFF {
+            return 0;
+        }
+        KEYS.en_passant[Pos::from_code(en_passant).x as usize]
+    }
+}
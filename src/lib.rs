@@ -1 +1,8 @@
-pub mod game;
\ No newline at end of file
+pub mod evaluation;
+pub mod game;
+pub mod limits;
+pub mod ordering;
+pub mod pst;
+pub mod transposition;
+pub mod uci;
+pub mod xboard;
@@ -0,0 +1,154 @@
+use crate::game::board::*;
+
+use std::time::{Duration, Instant};
+
+pub const MAX_SEARCH_DEPTH: usize = 64;
+// reserve for communication lag, never planned to be used
+pub const MOVE_OVERHEAD_MS: u64 = 50;
+// when number of moves to the next time control is unknown
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+// when search must stop, all times in milliseconds, None means no limit
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    // search until stopped from outside, other limits are ignored
+    pub infinite: bool,
+}
+
+// time allocated for one move
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TimeManager {
+    start: Instant,
+    // no new iteration is started after it
+    soft_limit: Option<Duration>,
+    // search is aborted in the middle of iteration after it
+    hard_limit: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn from_depth(depth: usize) -> Self {
+        SearchLimits {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_movetime(movetime: u64) -> Self {
+        SearchLimits {
+            movetime: Some(movetime),
+            ..Default::default()
+        }
+    }
+
+    // arguments of UCI "go": "wtime 60000 btime 60000 winc 1000 binc 1000", "depth 5", "infinite"
+    pub fn parse_uci(args: &[&str]) -> Self {
+        let mut res = SearchLimits::default();
+        let mut i = 0;
+        while i < args.len() {
+            let value = args.get(i + 1).and_then(|x| x.parse::<u64>().ok());
+            match args[i] {
+                "depth" => res.depth = value.map(|x| x as usize),
+                "nodes" => res.nodes = value,
+                "movetime" => res.movetime = value,
+                "wtime" => res.wtime = value,
+                "btime" => res.btime = value,
+                "winc" => res.winc = value,
+                "binc" => res.binc = value,
+                "movestogo" => res.movestogo = value,
+                "infinite" => {
+                    res.infinite = true;
+                    i += 1;
+                    continue;
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            i += 2;
+        }
+        res
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        if self.infinite {
+            return MAX_SEARCH_DEPTH;
+        }
+        self.depth
+            .unwrap_or(MAX_SEARCH_DEPTH)
+            .clamp(1, MAX_SEARCH_DEPTH)
+    }
+
+    pub fn get_max_nodes(&self) -> Option<u64> {
+        if self.infinite {
+            return None;
+        }
+        self.nodes
+    }
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits, turn: Color) -> Self {
+        let mut res = TimeManager {
+            start: Instant::now(),
+            soft_limit: None,
+            hard_limit: None,
+        };
+        if limits.infinite {
+            return res;
+        }
+        if let Some(x) = limits.movetime {
+            let time = Duration::from_millis(x.saturating_sub(MOVE_OVERHEAD_MS).max(1));
+            res.soft_limit = Some(time);
+            res.hard_limit = Some(time);
+            return res;
+        }
+        let (time, inc) = if turn == Color::White {
+            (limits.wtime, limits.winc.unwrap_or(0))
+        } else {
+            (limits.btime, limits.binc.unwrap_or(0))
+        };
+        let time = match time {
+            Some(x) => x,
+            None => return res,
+        };
+        let available = time.saturating_sub(MOVE_OVERHEAD_MS).max(1);
+        let moves_to_go = limits.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let optimum = (time / moves_to_go + inc * 3 / 4).min(available);
+        // next iteration usually takes longer than all previous ones together,
+        // so there is no point to start it after half of the optimum time
+        res.soft_limit = Some(Duration::from_millis((optimum / 2).max(1)));
+        // hard limit allows to finish an important iteration
+        res.hard_limit = Some(Duration::from_millis((optimum * 3).min(available)));
+        res
+    }
+
+    pub fn get_elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn get_deadline(&self) -> Option<Instant> {
+        self.hard_limit.map(|x| self.start + x)
+    }
+
+    pub fn get_soft_limit(&self) -> Option<Duration> {
+        self.soft_limit
+    }
+
+    pub fn get_hard_limit(&self) -> Option<Duration> {
+        self.hard_limit
+    }
+
+    // called after every finished iteration
+    pub fn should_stop(&self) -> bool {
+        self.soft_limit.is_some_and(|x| self.start.elapsed() >= x)
+    }
+}
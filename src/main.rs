@@ -1,45 +1,165 @@
+use ::rust_chess::evaluation::Evaluator;
 use ::rust_chess::game::board::*;
-use std::io::{self, Write};
+use ::rust_chess::game::history::*;
+use ::rust_chess::limits::SearchLimits;
+use ::rust_chess::uci::UciEngine;
+use ::rust_chess::xboard::XBoardEngine;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::Instant;
 // rnbqkbnr/1ppp2pp/4pp2/8/p1BPP3/2N2Q1N/PPP2PPP/R1B1K2R b KQk - 1 8
 // rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
 
-pub mod evaluation;
-use evaluation::Evaluator;
+// computer in console game thinks until one of these limits
+const CONSOLE_MAX_DEPTH: usize = 12;
+const CONSOLE_MOVE_TIME_MS: u64 = 5000;
+
 fn main() {
-    let mut board =
-        ChessBoardState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
-            .unwrap();
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(|x| x.as_str()) == Some("perft") {
+        run_perft(&args[2..]);
+        return;
+    }
+
+    let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+    // GUIs start talking UCI/CECP right away, so print the board only for humans
+    if io::stdin().is_terminal() {
+        board.debug_print();
+    }
+    let mut lines = io::stdin().lock().lines().map_while(Result::ok);
+    let first_line = match lines.next() {
+        Some(x) => x,
+        None => return,
+    };
+    if first_line.trim() == "uci" {
+        drop(lines);
+        let mut engine = UciEngine::new();
+        engine.handle_command(&first_line);
+        engine.run(io::stdin().lock());
+        return;
+    }
+    if first_line.trim() == "xboard" {
+        drop(lines);
+        let mut engine = XBoardEngine::new();
+        engine.handle_command(&first_line);
+        engine.run(io::BufReader::new(io::stdin()));
+        return;
+    }
+    run_console(Game::new(board), std::iter::once(first_line).chain(lines));
+}
+
+fn run_console(mut game: Game, mut lines: impl Iterator<Item = String>) {
     let mut eval = Evaluator::new();
-    board.debug_print();
     loop {
-        let mut mv;
+        let mv;
         loop {
-            let mut input = String::new();
-
-            io::stdin().read_line(&mut input).expect("Failed to read line");
-            mv = board.get_chess_move_from_string(&input[0..input.len() - 1]);
-
-            if mv.is_none() {
-                println!("Wrong move format");
-            } else if board.is_legal_move(mv.unwrap()) {
-                break;
-            } else {
-                println!("Move {} is illegal", board.get_move_string(mv.unwrap()));
-                io::stdout().flush().expect("Unable To Flush");
+            let input = match lines.next() {
+                Some(x) => x,
+                None => return,
+            };
+
+            let input = input.trim();
+            let board = game.get_board();
+            if input == "undo" {
+                // take back computer reply together with our move, only both or none
+                if game.get_moves().len() >= 2 {
+                    game.undo();
+                    game.undo();
+                    game.get_board().debug_print();
+                } else {
+                    println!("Nothing to undo");
+                }
+                continue;
+            }
+            let parsed = board
+                .get_chess_move_from_string(input)
+                .or_else(|| board.get_chess_move_from_san(input));
+            match parsed {
+                None => println!("Wrong move format"),
+                Some(x) if board.is_legal_move(x) => {
+                    mv = x;
+                    break;
+                }
+                Some(x) => {
+                    println!("Move {} is illegal", board.get_move_string(x));
+                    io::stdout().flush().expect("Unable To Flush");
+                }
             }
         }
-        board = board.get_new_pos_after_move(mv.unwrap());
-        board.debug_print();
+        game.push(mv);
+        game.get_board().debug_print();
+        if print_game_over(&game) {
+            return;
+        }
 
-        let res = eval.evaluate(&board, 12);
+        eval.set_history(game.get_previous_positions());
+        let limits = SearchLimits {
+            depth: Some(CONSOLE_MAX_DEPTH),
+            movetime: Some(CONSOLE_MOVE_TIME_MS),
+            ..Default::default()
+        };
+        let mut nodes = 0;
+        let computer_mv = eval
+            .search(game.get_board(), &limits, |info| nodes = info.nodes)
+            .unwrap();
         println!(
-            "Computer move {}; Position analysed {}",
-            board.get_move_string(res.1.last().unwrap().0),
-            eval.low_level_eval_called
+            "Computer move {}; Position analysed {}; Hash hits {:.1}%; First move cutoffs {:.1}%",
+            game.get_board().get_san_string(computer_mv),
+            nodes,
+            eval.get_tt_hit_rate() * 100.0,
+            eval.get_first_move_cutoff_rate() * 100.0
         );
 
-        board = board.get_new_pos_after_move(res.1.last().unwrap().0);
+        game.push(computer_mv);
 
-        board.debug_print();
+        game.get_board().debug_print();
+        if print_game_over(&game) {
+            return;
+        }
+    }
+}
+
+fn print_game_over(game: &Game) -> bool {
+    let status = game.get_status();
+    if let Some(result) = status.get_result() {
+        println!("{} {{{}}}", result.get_str(), status.get_description());
+        return true;
+    }
+    false
+}
+
+// perft <depth> [fen], prints node count for every root move as in "divide"
+fn run_perft(args: &[String]) {
+    let depth = match args.first().and_then(|x| x.parse::<usize>().ok()) {
+        Some(x) => x,
+        None => {
+            println!("Usage: rust_chess perft <depth> [fen]");
+            return;
+        }
+    };
+    let fen = if args.len() > 1 {
+        args[1..].join(" ")
+    } else {
+        START_POS_FEN.to_string()
+    };
+    let board = match ChessBoardState::try_from_fen(&fen) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("Wrong FEN: {}", e);
+            return;
+        }
+    };
+
+    let start = Instant::now();
+    let mut total = 0;
+    for (mv, nodes) in board.divide(depth) {
+        println!("{}: {}", mv.get_uci_string(), nodes);
+        total += nodes;
     }
+    let elapsed = start.elapsed();
+    println!("\nNodes searched: {}", total);
+    println!(
+        "Time: {} ms, {:.0} nodes/s",
+        elapsed.as_millis(),
+        total as f64 / elapsed.as_secs_f64()
+    );
 }
@@ -0,0 +1,158 @@
+use crate::evaluation::MAX_PLY;
+use crate::game::board::*;
+use crate::game::movegen::*;
+use crate::game::packed_move::*;
+use crate::game::rules::*;
+use crate::pst::*;
+
+// Order of moves in the main search, higher score goes first. Groups are far
+// apart, so history (kept below HISTORY_LIMIT) never moves a quiet move above a killer
+const HASH_MOVE_SCORE: i32 = 1 << 30;
+const CAPTURE_SCORE: i32 = 1 << 28;
+const PROMOTION_SCORE: i32 = 1 << 27;
+const KILLER_SCORE: i32 = 1 << 26;
+const COUNTER_MOVE_SCORE: i32 = 1 << 25;
+// captures that lose material by SEE go after the killers
+const LOSING_CAPTURE_SCORE: i32 = 1 << 24;
+const HISTORY_LIMIT: i32 = 1 << 20;
+
+pub const KILLERS_PER_PLY: usize = 2;
+
+// search statistics kept between searches and used to order quiet moves
+pub struct MoveOrdering {
+    // quiet moves that caused a beta cutoff at the same ply, newest first
+    killers: Vec<[PackedMove; KILLERS_PER_PLY]>,
+    // quiet move that refuted the previous move, indexed by its from and to squares
+    counter_moves: Vec<[PackedMove; BOARD_ARRAY_SIZE]>,
+    // butterfly table, cutoffs of quiet moves by side to move, from and to squares
+    history: Vec<[[i32; BOARD_ARRAY_SIZE]; BOARD_ARRAY_SIZE]>,
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        MoveOrdering {
+            killers: vec![[PackedMove::NULL; KILLERS_PER_PLY]; MAX_PLY],
+            counter_moves: vec![[PackedMove::NULL; BOARD_ARRAY_SIZE]; BOARD_ARRAY_SIZE],
+            history: vec![[[0; BOARD_ARRAY_SIZE]; BOARD_ARRAY_SIZE]; 2],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn get_killers(&self, ply: usize) -> [PackedMove; KILLERS_PER_PLY] {
+        self.killers[ply]
+    }
+
+    pub fn get_counter_move(&self, prev: PackedMove) -> PackedMove {
+        self.counter_moves[prev.get_from_idx()][prev.get_to_idx()]
+    }
+
+    pub fn get_history(&self, color: Color, mv: PackedMove) -> i32 {
+        self.history[color as usize][mv.get_from_idx()][mv.get_to_idx()]
+    }
+
+    // scores of moves in the same order, prev is the move that led to the position
+    // (PackedMove::NULL at the root)
+    pub fn score_moves(
+        &self,
+        board: &ChessBoardState,
+        moves: &MoveList,
+        hash_move: Option<PackedMove>,
+        ply: usize,
+        prev: PackedMove,
+    ) -> [i32; MAX_MOVES] {
+        let mut res = [0; MAX_MOVES];
+        let killers = self.get_killers(ply);
+        let counter_move = if prev.is_null() {
+            PackedMove::NULL
+        } else {
+            self.get_counter_move(prev)
+        };
+        for (i, &mv) in moves.iter().enumerate() {
+            res[i] = if hash_move == Some(mv) {
+                HASH_MOVE_SCORE
+            } else if mv.is_capture() {
+                Self::get_capture_score(board, mv)
+            } else if Self::is_queen_promotion(mv) {
+                PROMOTION_SCORE
+            } else if let Some(x) = killers.iter().position(|x| *x == mv) {
+                KILLER_SCORE - x as i32
+            } else if mv == counter_move {
+                COUNTER_MOVE_SCORE
+            } else {
+                self.get_history(board.turn, mv)
+            };
+        }
+        res
+    }
+
+    // winning and equal captures by MVV-LVA, losing ones by how much they lose
+    fn get_capture_score(board: &ChessBoardState, mv: PackedMove) -> i32 {
+        let attacker = board.get_piece_unsafe(mv.get_from());
+        let victim = board.get_piece_unsafe(mv.get_to());
+        // taking a piece worth at least the attacker can not lose material
+        if victim.get_see_value() < attacker.get_see_value() {
+            let see = board.see(mv.unpack());
+            if see < 0 {
+                return LOSING_CAPTURE_SCORE + see;
+            }
+        }
+        CAPTURE_SCORE + Self::get_mvv_lva(board, mv)
+    }
+
+    // most valuable victim first, of equal ones the one taken by the least valuable attacker
+    pub fn get_mvv_lva(board: &ChessBoardState, mv: PackedMove) -> i32 {
+        let attacker = board.get_piece_unsafe(mv.get_from());
+        let victim = match board.get_piece_unsafe(mv.get_to()) {
+            // en passant
+            ChessPiece::None => ChessPiece::PawnWhite,
+            x => x,
+        };
+        get_kind_idx(victim) as i32 * 8 - get_kind_idx(attacker) as i32
+    }
+
+    fn is_queen_promotion(mv: PackedMove) -> bool {
+        match mv.unpack().move_type {
+            ChessMoveType::Promotion(x) => {
+                x == ChessPiece::QueenWhite || x == ChessPiece::QueenBlack
+            }
+            _ => false,
+        }
+    }
+
+    // quiet move mv caused a beta cutoff, board is the position it was played in
+    pub fn update_cutoff(
+        &mut self,
+        board: &ChessBoardState,
+        mv: PackedMove,
+        ply: usize,
+        depth: usize,
+        prev: PackedMove,
+    ) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != mv {
+            killers[1] = killers[0];
+            killers[0] = mv;
+        }
+        if !prev.is_null() {
+            self.counter_moves[prev.get_from_idx()][prev.get_to_idx()] = mv;
+        }
+        let history = &mut self.history[board.turn as usize];
+        let entry = &mut history[mv.get_from_idx()][mv.get_to_idx()];
+        *entry += (depth * depth) as i32;
+        if *entry >= HISTORY_LIMIT {
+            // keep relative order while making room for new cutoffs
+            for x in history.iter_mut().flatten() {
+                *x /= 2;
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::game::packed_move::*;
+
+use std::mem;
+
+pub const DEFAULT_HASH_SIZE_MB: usize = 16;
+
+// what stored score means relative to the real one
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Bound {
+    Exact,
+    // search failed high, real score is not less
+    Lower,
+    // search failed low, real score is not greater
+    Upper,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TTEntry {
+    pub key: u64,
+    pub depth: u8,
+    // in centipawns, from the point of view of white as everywhere in Evaluator
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<PackedMove>,
+}
+
+// fixed size hash table indexed by the lowest bits of Zobrist key
+pub struct TranspositionTable {
+    entries: Vec<Option<TTEntry>>,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let mut res = TranspositionTable { entries: vec![] };
+        res.resize(size_mb);
+        res
+    }
+
+    // number of entries is rounded down to a power of two, so index is just a mask
+    pub fn resize(&mut self, size_mb: usize) {
+        let max_entries = (size_mb * 1024 * 1024 / mem::size_of::<Option<TTEntry>>()).max(1);
+        let len = 1 << max_entries.ilog2();
+        self.entries = vec![None; len];
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TTEntry> {
+        self.entries[self.get_idx(key)].filter(|x| x.key == key)
+    }
+
+    // other position in the slot is always replaced, same one only by deeper search
+    pub fn store(&mut self, entry: TTEntry) {
+        let idx = self.get_idx(entry.key);
+        match self.entries[idx] {
+            Some(x) if x.key == entry.key && x.depth > entry.depth => {}
+            _ => self.entries[idx] = Some(entry),
+        }
+    }
+
+    // permille of used entries, as in UCI "hashfull"
+    pub fn get_hashfull(&self) -> usize {
+        let sample = self.entries.len().min(1000);
+        let used = self.entries[..sample]
+            .iter()
+            .filter(|x| x.is_some())
+            .count();
+        used * 1000 / sample
+    }
+
+    fn get_idx(&self, key: u64) -> usize {
+        key as usize & (self.entries.len() - 1)
+    }
+}
@@ -0,0 +1,229 @@
+use crate::evaluation::{Evaluator, Score};
+use crate::game::board::*;
+use crate::game::history::*;
+use crate::limits::*;
+use crate::transposition::DEFAULT_HASH_SIZE_MB;
+
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+pub const ENGINE_NAME: &str = "RustChess";
+pub const ENGINE_AUTHOR: &str = "DanyaChan";
+pub const MAX_HASH_SIZE_MB: usize = 4096;
+pub const MAX_MULTIPV: usize = 64;
+
+pub struct UciEngine {
+    // moves from the position command are kept for repetition detection
+    game: Game,
+    // evaluator is moved into search thread while it is running
+    evaluator: Option<Evaluator>,
+    search: Option<JoinHandle<Evaluator>>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl Default for UciEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UciEngine {
+    pub fn new() -> Self {
+        let evaluator = Evaluator::new();
+        let stop_flag = evaluator.get_stop_flag();
+        UciEngine {
+            game: Game::from_fen(START_POS_FEN).unwrap(),
+            evaluator: Some(evaluator),
+            search: None,
+            stop_flag,
+        }
+    }
+
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            let line = match line {
+                Ok(x) => x,
+                Err(_) => break,
+            };
+            if !self.handle_command(&line) {
+                break;
+            }
+        }
+        self.stop_search();
+    }
+
+    // returns false when engine should quit
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first() {
+            Some(&"uci") => {
+                println!("id name {}", ENGINE_NAME);
+                println!("id author {}", ENGINE_AUTHOR);
+                println!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_HASH_SIZE_MB, MAX_HASH_SIZE_MB
+                );
+                println!(
+                    "option name MultiPV type spin default 1 min 1 max {}",
+                    MAX_MULTIPV
+                );
+                for name in ["NullMove", "LMR", "CheckExtensions"] {
+                    println!("option name {} type check default true", name);
+                }
+                println!("uciok");
+            }
+            Some(&"isready") => println!("readyok"),
+            Some(&"ucinewgame") => {
+                self.stop_search();
+                self.game = Game::from_fen(START_POS_FEN).unwrap();
+                self.evaluator.as_mut().unwrap().new_game();
+            }
+            Some(&"setoption") => {
+                self.stop_search();
+                self.set_option(&tokens[1..]);
+            }
+            Some(&"position") => {
+                self.stop_search();
+                match Self::parse_position(&tokens[1..]) {
+                    Some(x) => self.game = x,
+                    None => println!("info string invalid position command"),
+                }
+            }
+            Some(&"go") => {
+                self.stop_search();
+                self.start_search(SearchLimits::parse_uci(&tokens[1..]));
+            }
+            Some(&"stop") => self.stop_search(),
+            Some(&"quit") => return false,
+            _ => {}
+        }
+        true
+    }
+
+    pub fn get_board(&self) -> &ChessBoardState {
+        self.game.get_board()
+    }
+
+    // "name <name> value <value>", names are case insensitive
+    fn set_option(&mut self, args: &[&str]) {
+        let value_idx = args
+            .iter()
+            .position(|x| *x == "value")
+            .unwrap_or(args.len());
+        if args.first() != Some(&"name") {
+            println!("info string invalid setoption command");
+            return;
+        }
+        let name = args[1..value_idx].join(" ").to_lowercase();
+        let value = args.get(value_idx + 1..).unwrap_or(&[]).join(" ");
+        match name.as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(x) if (1..=MAX_HASH_SIZE_MB).contains(&x) => {
+                    self.evaluator.as_mut().unwrap().set_hash_size(x)
+                }
+                _ => println!("info string invalid Hash value {}", value),
+            },
+            "multipv" => match value.parse::<usize>() {
+                Ok(x) if (1..=MAX_MULTIPV).contains(&x) => {
+                    self.evaluator.as_mut().unwrap().set_multipv(x)
+                }
+                _ => println!("info string invalid MultiPV value {}", value),
+            },
+            "nullmove" | "lmr" | "checkextensions" => {
+                let enabled = match value.as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => {
+                        println!("info string invalid {} value {}", name, value);
+                        return;
+                    }
+                };
+                let evaluator = self.evaluator.as_mut().unwrap();
+                match name.as_str() {
+                    "nullmove" => evaluator.set_null_move_pruning(enabled),
+                    "lmr" => evaluator.set_late_move_reductions(enabled),
+                    _ => evaluator.set_check_extensions(enabled),
+                }
+            }
+            _ => println!("info string unknown option {}", name),
+        }
+    }
+
+    // "startpos moves e2e4 e7e5" or "fen <fen> moves ..."
+    pub fn parse_position(args: &[&str]) -> Option<Game> {
+        let moves_idx = args
+            .iter()
+            .position(|x| *x == "moves")
+            .unwrap_or(args.len());
+        let mut game = match args.first() {
+            Some(&"startpos") => Game::from_fen(START_POS_FEN)?,
+            Some(&"fen") => Game::from_fen(&args[1..moves_idx].join(" "))?,
+            _ => return None,
+        };
+        for mv_str in args.iter().skip(moves_idx + 1) {
+            let mv = game.get_board().get_chess_move_from_uci(mv_str)?;
+            game.push(mv);
+        }
+        Some(game)
+    }
+
+    fn start_search(&mut self, limits: SearchLimits) {
+        let board = *self.game.get_board();
+        let mut evaluator = self.evaluator.take().unwrap();
+        evaluator.set_history(self.game.get_previous_positions());
+        self.stop_flag.store(false, Ordering::Relaxed);
+        let stop_flag = self.stop_flag.clone();
+        self.search = Some(thread::spawn(move || {
+            Self::search(&mut evaluator, &board, limits, &stop_flag);
+            evaluator
+        }));
+    }
+
+    fn stop_search(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.search.take() {
+            self.evaluator = Some(handle.join().unwrap());
+        }
+    }
+
+    fn search(
+        evaluator: &mut Evaluator,
+        board: &ChessBoardState,
+        limits: SearchLimits,
+        stop_flag: &AtomicBool,
+    ) {
+        let best_move = evaluator.search(board, &limits, |info| {
+            // one line for every root move, multipv 1 is the best one
+            for (i, line) in info.lines.iter().enumerate() {
+                println!(
+                    "info depth {} multipv {} score {} nodes {} time {} pv {}",
+                    info.depth,
+                    i + 1,
+                    match line.get_score() {
+                        Score::Centipawns(x) => format!("cp {}", x),
+                        Score::Mate(x) => format!("mate {}", x),
+                    },
+                    info.nodes,
+                    info.time.as_millis(),
+                    line.pv
+                        .iter()
+                        .map(|x| x.get_uci_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+            }
+        });
+
+        // in infinite mode bestmove must be sent only after "stop"
+        while limits.infinite && !stop_flag.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        match best_move {
+            Some(x) => println!("bestmove {}", x.get_uci_string()),
+            None => println!("bestmove 0000"),
+        }
+    }
+}
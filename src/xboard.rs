@@ -0,0 +1,343 @@
+use crate::evaluation::{Evaluator, Score};
+use crate::game::board::*;
+use crate::game::history::*;
+use crate::game::rules::*;
+use crate::limits::*;
+use crate::uci::{ENGINE_NAME, MAX_HASH_SIZE_MB};
+
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+// both input lines and finished searches come through one channel,
+// so the engine can answer commands while thinking
+enum Event {
+    Command(String),
+    InputClosed,
+    SearchDone(Box<Evaluator>, Option<ChessMove>),
+}
+
+// "level MPS BASE INC", all times in milliseconds
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TimeControl {
+    pub moves_per_session: u32,
+    pub base: u64,
+    pub increment: u64,
+}
+
+pub struct XBoardEngine {
+    // played moves are kept for undo/remove and repetition detection
+    game: Game,
+    // None means force mode
+    engine_color: Option<Color>,
+    post: bool,
+    max_depth: usize,
+    fixed_move_time: Option<u64>,
+    time_control: Option<TimeControl>,
+    // engine clock from "time" command
+    time_left: Option<u64>,
+
+    // evaluator is moved into search thread while it is running
+    evaluator: Option<Evaluator>,
+    searching: bool,
+    discard_search: bool,
+    stop_flag: Arc<AtomicBool>,
+    pending: VecDeque<String>,
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+}
+
+impl TimeControl {
+    // "40 5 0", "0 2:30 1"
+    pub fn parse(args: &[&str]) -> Option<Self> {
+        if args.len() < 3 {
+            return None;
+        }
+        let moves_per_session = args[0].parse::<u32>().ok()?;
+        let base = match args[1].split_once(':') {
+            Some((min, sec)) => min.parse::<u64>().ok()? * 60 + sec.parse::<u64>().ok()?,
+            None => args[1].parse::<u64>().ok()? * 60,
+        };
+        let increment = (args[2].parse::<f64>().ok()? * 1000.0) as u64;
+        Some(TimeControl {
+            moves_per_session,
+            base: base * 1000,
+            increment,
+        })
+    }
+}
+
+impl Default for XBoardEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XBoardEngine {
+    pub fn new() -> Self {
+        let evaluator = Evaluator::new();
+        let stop_flag = evaluator.get_stop_flag();
+        let (sender, receiver) = mpsc::channel();
+        XBoardEngine {
+            game: Game::from_fen(START_POS_FEN).unwrap(),
+            engine_color: Some(Color::Black),
+            post: false,
+            max_depth: MAX_SEARCH_DEPTH,
+            fixed_move_time: None,
+            time_control: None,
+            time_left: None,
+            evaluator: Some(evaluator),
+            searching: false,
+            discard_search: false,
+            stop_flag,
+            pending: VecDeque::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn run<R: BufRead + Send + 'static>(&mut self, input: R) {
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            for line in input.lines().map_while(Result::ok) {
+                if sender.send(Event::Command(line)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(Event::InputClosed);
+        });
+
+        loop {
+            let event = match self.pending.pop_front() {
+                Some(x) => Event::Command(x),
+                None => self.receiver.recv().unwrap(),
+            };
+            match event {
+                Event::Command(x) => {
+                    if !self.handle_command(&x) {
+                        break;
+                    }
+                }
+                Event::InputClosed => break,
+                Event::SearchDone(evaluator, mv) => self.finish_search(*evaluator, mv),
+            }
+        }
+        self.abort_search();
+    }
+
+    pub fn get_board(&self) -> &ChessBoardState {
+        self.game.get_board()
+    }
+
+    // returns false when engine should quit
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let command = match tokens.first() {
+            Some(x) => *x,
+            None => return true,
+        };
+        if self.searching {
+            match command {
+                "?" => {
+                    self.stop_flag.store(true, Ordering::Relaxed);
+                    return true;
+                }
+                "quit" | "new" | "force" | "result" | "setboard" | "undo" | "remove" => {
+                    self.abort_search()
+                }
+                "ping" | "usermove" | "go" | "level" | "st" | "sd" | "memory" => self.wait_search(),
+                _ => {}
+            }
+        }
+        match command {
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" | "otim" => {}
+            "protover" => println!(
+                "feature myname=\"{}\" ping=1 setboard=1 usermove=1 memory=1 san=0 colors=0 sigint=0 sigterm=0 analyze=0 done=1",
+                ENGINE_NAME
+            ),
+            "new" => {
+                self.game = Game::from_fen(START_POS_FEN).unwrap();
+                self.evaluator.as_mut().unwrap().new_game();
+                self.engine_color = Some(Color::Black);
+                self.max_depth = MAX_SEARCH_DEPTH;
+                self.fixed_move_time = None;
+            }
+            "force" | "result" => self.engine_color = None,
+            "go" => {
+                self.engine_color = Some(self.game.get_board().turn);
+                self.start_search();
+            }
+            "usermove" => match tokens.get(1) {
+                Some(x) => self.apply_user_move(x),
+                None => println!("Error (no move): {}", line),
+            },
+            "setboard" => match ChessBoardState::try_from_fen(&tokens[1..].join(" ")) {
+                Ok(x) => self.game = Game::new(x),
+                Err(e) => println!("tellusererror Illegal position: {}", e),
+            },
+            "level" => match TimeControl::parse(&tokens[1..]) {
+                Some(x) => {
+                    self.time_control = Some(x);
+                    self.fixed_move_time = None;
+                }
+                None => println!("Error (bad arguments): {}", line),
+            },
+            "st" => match tokens.get(1).and_then(|x| x.parse::<u64>().ok()) {
+                Some(x) => self.fixed_move_time = Some(x * 1000),
+                None => println!("Error (bad arguments): {}", line),
+            },
+            "sd" => match tokens.get(1).and_then(|x| x.parse::<usize>().ok()) {
+                Some(x) => self.max_depth = x.max(1),
+                None => println!("Error (bad arguments): {}", line),
+            },
+            "memory" => match tokens.get(1).and_then(|x| x.parse::<usize>().ok()) {
+                Some(x) => self
+                    .evaluator
+                    .as_mut()
+                    .unwrap()
+                    .set_hash_size(x.clamp(1, MAX_HASH_SIZE_MB)),
+                None => println!("Error (bad arguments): {}", line),
+            },
+            // centiseconds
+            "time" => self.time_left = tokens.get(1).and_then(|x| x.parse::<u64>().ok()).map(|x| x * 10),
+            "undo" => self.take_back(1),
+            "remove" => self.take_back(2),
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "ping" => println!("pong {}", tokens.get(1).unwrap_or(&"")),
+            "?" => {}
+            "quit" => return false,
+            _ => println!("Error (unknown command): {}", command),
+        }
+        true
+    }
+
+    fn apply_user_move(&mut self, move_str: &str) {
+        match self.game.get_board().get_chess_move_from_uci(move_str) {
+            Some(mv) => {
+                self.play_move(mv);
+                if !self.game.get_status().is_over()
+                    && self.engine_color == Some(self.game.get_board().turn)
+                {
+                    self.start_search();
+                }
+            }
+            None => println!("Illegal move: {}", move_str),
+        }
+    }
+
+    fn play_move(&mut self, mv: ChessMove) {
+        self.game.push(mv);
+        let status = self.game.get_status();
+        if let Some(result) = status.get_result() {
+            println!("{} {{{}}}", result.get_str(), status.get_description());
+            // game ended by rule, engine plays again only after "new" or "go"
+            self.engine_color = None;
+        }
+    }
+
+    fn take_back(&mut self, plies: usize) {
+        for _ in 0..plies {
+            self.game.pop();
+        }
+    }
+
+    // "st" wins over "level", engine clock is given to the side to move
+    fn get_search_limits(&self) -> SearchLimits {
+        let mut res = SearchLimits::from_depth(self.max_depth);
+        if let Some(x) = self.fixed_move_time {
+            res.movetime = Some(x);
+            return res;
+        }
+        let tc = match self.time_control {
+            Some(x) => x,
+            None => return res,
+        };
+        let time_left = Some(self.time_left.unwrap_or(tc.base));
+        if tc.moves_per_session > 0 {
+            let played = (self.game.get_board().move_num as u32 - 1) % tc.moves_per_session;
+            res.movestogo = Some((tc.moves_per_session - played) as u64);
+        }
+        if self.game.get_board().turn == Color::White {
+            res.wtime = time_left;
+            res.winc = Some(tc.increment);
+        } else {
+            res.btime = time_left;
+            res.binc = Some(tc.increment);
+        }
+        res
+    }
+
+    fn start_search(&mut self) {
+        let board = *self.game.get_board();
+        let mut evaluator = self.evaluator.take().unwrap();
+        evaluator.set_history(self.game.get_previous_positions());
+        let limits = self.get_search_limits();
+        let post = self.post;
+        let sender = self.sender.clone();
+        self.stop_flag.store(false, Ordering::Relaxed);
+        self.searching = true;
+        self.discard_search = false;
+        thread::spawn(move || {
+            let mv = evaluator.search(&board, &limits, |info| {
+                if post {
+                    println!(
+                        "{} {} {} {} {}",
+                        info.depth,
+                        // mate in n is reported as 100000 + n
+                        match info.get_score() {
+                            Score::Centipawns(x) => x,
+                            Score::Mate(x) if x > 0 => 100000 + x,
+                            Score::Mate(x) => -100000 + x,
+                        },
+                        info.time.as_millis() / 10,
+                        info.nodes,
+                        info.pv
+                            .iter()
+                            .map(|x| x.get_uci_string())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    );
+                }
+            });
+            let _ = sender.send(Event::SearchDone(Box::new(evaluator), mv));
+        });
+    }
+
+    fn finish_search(&mut self, evaluator: Evaluator, mv: Option<ChessMove>) {
+        self.evaluator = Some(evaluator);
+        self.searching = false;
+        if self.discard_search {
+            return;
+        }
+        // "go" in a finished game still searches, but there is no move to play
+        if self.game.get_status().is_over() {
+            self.engine_color = None;
+            return;
+        }
+        if let Some(x) = mv {
+            println!("move {}", x.get_uci_string());
+            self.play_move(x);
+        }
+    }
+
+    // blocks until search thread returns, commands received meanwhile are handled later
+    fn wait_search(&mut self) {
+        while self.searching {
+            match self.receiver.recv().unwrap() {
+                Event::Command(x) => self.pending.push_back(x),
+                Event::InputClosed => self.pending.push_back("quit".to_string()),
+                Event::SearchDone(evaluator, mv) => self.finish_search(*evaluator, mv),
+            }
+        }
+    }
+
+    fn abort_search(&mut self) {
+        self.discard_search = true;
+        self.stop_flag.store(true, Ordering::Relaxed);
+        self.wait_search();
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::game::board::*;
+
+    #[test]
+    fn test_bishop_attacks_all_diagonals() {
+        // one bishop on each side of d4
+        let fens = [
+            "4k3/8/8/8/3K4/8/8/b7 w - - 0 1",
+            "4k3/8/8/8/3K4/8/8/6b1 w - - 0 1",
+            "4k3/6b1/8/8/3K4/8/8/8 w - - 0 1",
+            "4k3/b7/8/8/3K4/8/8/8 w - - 0 1",
+        ];
+        for fen in fens {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            assert!(
+                board.get_pos_attacked(Pos::from_str("d4"), Color::White),
+                "{}",
+                fen
+            );
+            assert!(board.get_king_attacked(Color::White), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_black_king_in_check() {
+        let board = ChessBoardState::from_fen("4k3/8/8/8/8/8/8/4K2R b - - 0 1").unwrap();
+        assert!(board.get_king_pos(Color::Black) == Pos::from_str("e8"));
+        assert!(!board.get_king_attacked(Color::Black));
+        // rook checks along the last rank, only stepping off it is legal
+        let board = ChessBoardState::from_fen("4k2R/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(board.get_king_attacked(Color::Black));
+        let moves = board.get_all_moves_checked();
+        assert_eq!(moves.len(), 3);
+        assert!(moves.iter().all(|x| x.mv.to.y == 6));
+    }
+}
@@ -0,0 +1,55 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use ::rust_chess::game::bitboard::*;
+    use ::rust_chess::game::board::*;
+
+    fn squares(names: &[&str]) -> Bitboard {
+        names
+            .iter()
+            .fold(0, |acc, x| acc | square_bb(Pos::from_str(x)))
+    }
+
+    #[test]
+    fn test_attacks() {
+        let d4 = ChessBoardState::get_pos_idx(Pos::from_str("d4"));
+        let a1 = ChessBoardState::get_pos_idx(Pos::from_str("a1"));
+        assert_eq!(knight_attacks(a1), squares(&["b3", "c2"]));
+        assert_eq!(king_attacks(a1), squares(&["a2", "b1", "b2"]));
+        assert_eq!(pawn_attacks(Color::White, d4), squares(&["c5", "e5"]));
+        assert_eq!(pawn_attacks(Color::Black, d4), squares(&["c3", "e3"]));
+
+        // blockers are attacked, squares behind them are not
+        let occupied = squares(&["d6", "b4", "d2", "f6", "h4"]);
+        assert_eq!(
+            rook_attacks(d4, occupied),
+            squares(&["d5", "d6", "d3", "d2", "c4", "b4", "e4", "f4", "g4", "h4"])
+        );
+        assert_eq!(
+            bishop_attacks(d4, occupied),
+            squares(&["e5", "f6", "c5", "b6", "a7", "c3", "b2", "a1", "e3", "f2", "g1"])
+        );
+        assert_eq!(rook_attacks(a1, !0), squares(&["a2", "b1"]));
+        assert_eq!(rook_attacks(a1, 0).count_ones(), 14);
+        assert_eq!(queen_attacks(d4, 0).count_ones(), 27);
+    }
+
+    #[test]
+    fn test_bitboards_follow_board() {
+        let fens = ["r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"];
+        random_positions(&fens, 777, 4, 50, |cur, _| {
+            for (i, piece) in cur.board.iter().enumerate() {
+                assert_ne!(cur.get_piece_bb(*piece) & (1 << i), 0);
+                let color_bb = match piece.get_color() {
+                    Some(x) => cur.get_color_bb(x),
+                    None => !cur.get_occupied_bb(),
+                };
+                assert_ne!(color_bb & (1 << i), 0);
+            }
+            let total: u32 = cur.pieces_bb.iter().map(|x| x.count_ones()).sum();
+            assert_eq!(total, 64);
+        });
+    }
+}
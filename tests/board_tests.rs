@@ -27,6 +27,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn test_load_fen_default() {
         let fen = ChessBoardState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
         assert_eq!(fen.turn, Color::White);
@@ -50,6 +51,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop, clippy::unnecessary_cast)]
     fn test_load_fen() {
         let fen = ChessBoardState::from_fen("r1b1kbnr/ppp3p1/2n5/1B1qppPp/3P3N/2N1B3/PPP2P1P/R2QK2R w Kq h6 1 38").unwrap();
         assert_eq!(fen.turn, Color::White);
@@ -71,4 +73,81 @@ mod tests {
             assert_eq!(fen.board[i], expected[i]);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fen_round_trip() {
+        let fens = [
+            START_POS_FEN,
+            "r1b1kbnr/ppp3p1/2n5/1B1qppPp/3P3N/2N1B3/PPP2P1P/R2QK2R w Kq h6 1 38",
+            "r3k2r/p1pp1pb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+            "4k3/8/8/8/8/8/8/4K3 b - - 99 150",
+        ];
+        for fen in fens {
+            assert_eq!(ChessBoardState::from_fen(fen).unwrap().to_fen(), fen);
+        }
+        // clocks may be omitted
+        assert_eq!(
+            ChessBoardState::from_fen("4k3/8/8/8/8/8/8/4K3 w - -")
+                .unwrap()
+                .to_fen(),
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_fen_errors() {
+        let cases = [
+            ("4k3/8/8/8/8/8/8/4K3 w -", FenError::WrongFieldCount(3)),
+            ("4k3/8/8/8/8/8/4K3 w - - 0 1", FenError::WrongRankCount(7)),
+            ("4k3/8/8/8/8/8/8/4K4 w - - 0 1", FenError::RankOverflow(1)),
+            ("4k3/8/8/8/8/8/8/4K2 w - - 0 1", FenError::RankTooShort(1)),
+            (
+                "4k3/8/8/8/8/8/8/4X3 w - - 0 1",
+                FenError::UnexpectedChar(FenField::Board, 'X'),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+                FenError::UnexpectedChar(FenField::Turn, 'x'),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w A - 0 1",
+                FenError::UnexpectedChar(FenField::Castling, 'A'),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w K - 0 1",
+                FenError::ImpossibleCastling('K'),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - z3 0 1",
+                FenError::UnexpectedChar(FenField::EnPassant, 'z'),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+                FenError::ImpossibleEnPassant,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+                FenError::WrongNumber(FenField::HalfmoveClock),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+                FenError::WrongNumber(FenField::MoveNumber),
+            ),
+            (
+                "8/8/8/8/8/8/8/4K3 w - - 0 1",
+                FenError::MissingKing(Color::Black),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/3KK3 w - - 0 1",
+                FenError::TooManyKings(Color::White),
+            ),
+            ("4k2P/8/8/8/8/8/8/4K3 w - - 0 1", FenError::PawnOnBackRank),
+            ("4k3/8/8/8/8/8/8/4K2r b - - 0 1", FenError::OpponentInCheck),
+        ];
+        for (fen, err) in cases {
+            assert_eq!(ChessBoardState::try_from_fen(fen), Err(err), "{}", fen);
+        }
+    }
+}
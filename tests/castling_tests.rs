@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::rules::*;
+
+    #[test]
+    fn test_long_castle_target() {
+        let fens = [
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "c1", "d1"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "c8", "d8"),
+        ];
+        for (fen, king_to, rook_to) in fens {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let mv = board
+                .get_all_moves_checked()
+                .into_iter()
+                .find(|x| x.move_type == ChessMoveType::CastleLong)
+                .unwrap();
+            assert!(mv.mv.to == Pos::from_str(king_to), "{}", fen);
+            let new_board = board.get_new_pos_after_move(mv);
+            let king = new_board.get_piece_unsafe(Pos::from_str(king_to));
+            let rook = new_board.get_piece_unsafe(Pos::from_str(rook_to));
+            assert!(
+                king == ChessPiece::KingWhite || king == ChessPiece::KingBlack,
+                "{}",
+                fen
+            );
+            assert!(
+                rook == ChessPiece::RookWhite || rook == ChessPiece::RookBlack,
+                "{}",
+                fen
+            );
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use ::rust_chess::game::board::*;
+use ::rust_chess::game::rules::*;
+
+// Plays games of random legal moves, each of them up to plies long, from every fen.
+// check gets every position on the way and the move played from it, None for the last
+// position of a game. The seed is fixed by the caller so failures can be reproduced
+pub fn random_positions<F>(fens: &[&str], seed: u64, games: usize, plies: usize, mut check: F)
+where
+    F: FnMut(&ChessBoardState, Option<ChessMove>),
+{
+    let mut seed = seed;
+    for fen in fens {
+        for _ in 0..games {
+            let mut board = ChessBoardState::from_fen(fen).unwrap();
+            for _ in 0..plies {
+                let moves = board.get_legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let mv = moves[(seed >> 33) as usize % moves.len()].unpack();
+                check(&board, Some(mv));
+                board = board.get_new_pos_after_move(mv);
+            }
+            check(&board, None);
+        }
+    }
+}
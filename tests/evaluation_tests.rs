@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::status::*;
+
+    #[test]
+    fn test_mate_in_one() {
+        let board = ChessBoardState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut evaluator = Evaluator::new();
+        let res = evaluator.evaluate(&board, 2);
+        assert_eq!(res.best_move.unwrap().get_uci_string(), "a1a8");
+        assert_eq!(res.get_score(), Score::Mate(1));
+    }
+
+    #[test]
+    fn test_pv_is_played_line() {
+        let mut evaluator = Evaluator::new();
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        let res = evaluator.evaluate(&board, 4);
+        assert_eq!(res.depth, 4);
+        assert!(res.pv.len() >= 2);
+        assert_eq!(res.best_move, res.pv.first().map(|x| x.unpack()));
+        let mut cur = board;
+        for mv in &res.pv {
+            assert!(cur.get_legal_moves().contains(mv));
+            cur = cur.get_new_pos_after_move(mv.unpack());
+        }
+
+        // line ends with the mate
+        let board = ChessBoardState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let res = evaluator.evaluate(&board, 2);
+        assert_eq!(res.pv.len(), 1);
+        let cur = board.get_new_pos_after_move(res.pv[0].unpack());
+        assert!(matches!(cur.get_game_status(), GameStatus::Checkmate(_)));
+    }
+
+    #[test]
+    fn test_mate_distance() {
+        assert_eq!(Score::from_eval(50), Score::Centipawns(50));
+        assert_eq!(Score::from_eval(MATE_SCORE - 3), Score::Mate(2));
+        assert_eq!(Score::from_eval(-MATE_SCORE + 2), Score::Mate(-1));
+
+        let mut evaluator = Evaluator::new();
+        let board = ChessBoardState::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        for depth in [5, 7] {
+            let res = evaluator.evaluate(&board, depth);
+            assert_eq!(res.get_score(), Score::Mate(2));
+        }
+
+        let board = ChessBoardState::from_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1").unwrap();
+        let res = evaluator.evaluate(&board, 2);
+        assert_eq!(res.get_score(), Score::Mate(-1));
+        assert_eq!(res.pv.len(), 2);
+    }
+
+    #[test]
+    fn test_pv_after_previous_search() {
+        // table entries from the first search must not cut the line of the second one
+        let board = ChessBoardState::from_fen(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 b - - 0 10",
+        )
+        .unwrap();
+        let mut evaluator = Evaluator::new();
+        for depth in [5, 6, 6] {
+            let res = evaluator.evaluate(&board, depth);
+            assert!(res.pv.len() + 1 >= depth, "{} {}", depth, res.pv.len());
+        }
+    }
+
+    #[test]
+    fn test_stalemate_is_draw() {
+        let board = ChessBoardState::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        let mut evaluator = Evaluator::new();
+        assert_eq!(evaluator.evaluate(&board, 3).score, 0);
+
+        // queen move to d5 would stalemate, so it must not be chosen
+        let board = ChessBoardState::from_fen("7k/5Q2/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+        let res = evaluator.evaluate(&board, 2);
+        assert_ne!(res.best_move.unwrap().get_uci_string(), "f7d5");
+        assert!(res.score > 0);
+    }
+
+    #[test]
+    fn test_quiescence_sees_recapture() {
+        // pawn on d5 is defended, taking it with the queen loses the queen
+        let board = ChessBoardState::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let mut evaluator = Evaluator::new();
+        for evasions in [true, false] {
+            evaluator.set_qsearch_check_evasions(evasions);
+            let res = evaluator.evaluate(&board, 1);
+            assert_ne!(res.best_move.unwrap().get_uci_string(), "d1d5");
+            assert!(res.score > 500);
+        }
+
+        // undefended pawn is won even at the horizon
+        let board = ChessBoardState::from_fen("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let res = evaluator.evaluate(&board, 2);
+        assert!(res.score > 900);
+    }
+}
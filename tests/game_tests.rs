@@ -1,5 +1,6 @@
 mod tests {
     use ::rust_chess::game::board::*;
+    use ::rust_chess::game::status::*;
     #[test]
     fn test_all_moves() {
         let board = ChessBoardState::from_fen(
@@ -89,4 +90,62 @@ mod tests {
     //     // }
     //     assert_eq!(expected.len(), result.len());
     // }
+
+    #[test]
+    fn test_game_status() {
+        let cases = [
+            (START_POS_FEN, GameStatus::Ongoing),
+            // fool's mate
+            (
+                "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+                GameStatus::Checkmate(Color::Black),
+            ),
+            (
+                "R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+                GameStatus::Checkmate(Color::White),
+            ),
+            ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", GameStatus::Stalemate),
+            (
+                "8/8/4k3/8/8/3K4/8/8 w - - 0 1",
+                GameStatus::InsufficientMaterial,
+            ),
+            (
+                "8/8/4k3/8/8/3K4/5N2/8 w - - 0 1",
+                GameStatus::InsufficientMaterial,
+            ),
+            (
+                "8/2b5/4k3/8/8/3K4/5B2/8 w - - 0 1",
+                GameStatus::InsufficientMaterial,
+            ),
+            ("8/3b4/4k3/8/8/3K4/5B2/8 w - - 0 1", GameStatus::Ongoing),
+            ("8/8/4k3/8/8/3K4/4NN2/8 w - - 0 1", GameStatus::Ongoing),
+            ("8/8/4k3/8/8/3K4/5R2/8 w - - 99 80", GameStatus::Ongoing),
+            (
+                "8/8/4k3/8/8/3K4/5R2/8 w - - 100 80",
+                GameStatus::FiftyMoveRule,
+            ),
+            (
+                "8/8/4k3/8/8/3K4/5R2/8 w - - 150 80",
+                GameStatus::SeventyFiveMoveRule,
+            ),
+            // mate has priority over move rules
+            (
+                "R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80",
+                GameStatus::Checkmate(Color::White),
+            ),
+        ];
+        for (fen, status) in cases {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            assert_eq!(board.get_game_status(), status, "{}", fen);
+        }
+        assert_eq!(
+            GameStatus::Checkmate(Color::Black).get_result(),
+            Some(GameResult::BlackWins)
+        );
+        assert_eq!(
+            GameStatus::Stalemate.get_result().unwrap().get_str(),
+            "1/2-1/2"
+        );
+        assert_eq!(GameStatus::Ongoing.get_result(), None);
+    }
 }
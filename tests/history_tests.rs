@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::history::*;
+    use ::rust_chess::game::status::*;
+
+    fn push_uci(game: &mut Game, moves: &[&str]) {
+        for mv_str in moves {
+            let mv = game.get_board().get_chess_move_from_uci(mv_str).unwrap();
+            assert!(game.push(mv));
+        }
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut game = Game::from_fen(START_POS_FEN).unwrap();
+        push_uci(&mut game, &["e2e4", "e7e5", "g1f3"]);
+        let after = *game.get_board();
+
+        assert_eq!(game.undo().unwrap().get_uci_string(), "g1f3");
+        assert_eq!(game.undo().unwrap().get_uci_string(), "e7e5");
+        assert_eq!(game.get_moves().len(), 1);
+        assert_eq!(game.redo().unwrap().get_uci_string(), "e7e5");
+        assert_eq!(game.redo().unwrap().get_uci_string(), "g1f3");
+        assert_eq!(game.redo(), None);
+        assert_eq!(*game.get_board(), after);
+
+        // new move drops moves that could be redone
+        game.undo();
+        push_uci(&mut game, &["b1c3"]);
+        assert_eq!(game.redo(), None);
+
+        assert_eq!(game.pop().unwrap().get_uci_string(), "b1c3");
+        assert_eq!(game.redo(), None);
+        let illegal = game
+            .get_board()
+            .get_chess_move_from_string("e4-e6")
+            .unwrap();
+        assert!(!game.push(illegal));
+        assert_eq!(game.get_positions().len(), 3);
+    }
+
+    #[test]
+    fn test_repetition() {
+        let mut game = Game::from_fen(START_POS_FEN).unwrap();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        push_uci(&mut game, &shuffle);
+        assert_eq!(game.get_repetition_count(), 2);
+        assert_eq!(game.get_status(), GameStatus::Ongoing);
+
+        push_uci(&mut game, &shuffle);
+        assert!(game.is_threefold_repetition());
+        assert_eq!(game.get_status(), GameStatus::ThreefoldRepetition);
+        assert_eq!(game.get_status().get_result(), Some(GameResult::Draw));
+
+        push_uci(&mut game, &shuffle[..3]);
+        assert_eq!(game.get_repetition_count(), 3);
+        push_uci(&mut game, &shuffle[3..]);
+        push_uci(&mut game, &shuffle);
+        assert!(game.is_fivefold_repetition());
+        assert_eq!(game.get_status(), GameStatus::FivefoldRepetition);
+
+        // pawn move makes previous positions unreachable
+        push_uci(&mut game, &["e2e4"]);
+        assert_eq!(game.get_repetition_count(), 1);
+    }
+
+    #[test]
+    fn test_search_scores_repetition_as_draw() {
+        let mut game = Game::from_fen("6k1/8/8/8/8/8/8/K1Q5 b - - 0 1").unwrap();
+        push_uci(&mut game, &["g8h8", "c1c2", "h8g8", "c2c1"]);
+
+        // without history black king goes to the center
+        let mut evaluator = Evaluator::new();
+        let res = evaluator.evaluate(game.get_board(), 1);
+        assert_ne!(res.best_move.unwrap().get_uci_string(), "g8h8");
+
+        // lost side repeats the position
+        evaluator.set_history(game.get_previous_positions());
+        let res = evaluator.evaluate(game.get_board(), 1);
+        assert_eq!(res.best_move.unwrap().get_uci_string(), "g8h8");
+        assert_eq!(res.score, 0);
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::limits::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_parse_uci() {
+        let args: Vec<&str> = "wtime 60000 btime 30000 winc 1000 binc 500 movestogo 20 nodes 1000"
+            .split_whitespace()
+            .collect();
+        let limits = SearchLimits::parse_uci(&args);
+        assert_eq!(limits.wtime, Some(60000));
+        assert_eq!(limits.btime, Some(30000));
+        assert_eq!(limits.winc, Some(1000));
+        assert_eq!(limits.binc, Some(500));
+        assert_eq!(limits.movestogo, Some(20));
+        assert_eq!(limits.get_max_nodes(), Some(1000));
+        assert_eq!(limits.get_max_depth(), MAX_SEARCH_DEPTH);
+
+        let limits = SearchLimits::parse_uci(&["infinite", "depth", "3"]);
+        assert!(limits.infinite);
+        assert_eq!(limits.get_max_depth(), MAX_SEARCH_DEPTH);
+        assert_eq!(SearchLimits::parse_uci(&["depth", "3"]).get_max_depth(), 3);
+    }
+
+    #[test]
+    fn test_time_allocation() {
+        let limits = SearchLimits::parse_uci(&["wtime", "60000", "btime", "1000"]);
+        let white = TimeManager::new(&limits, Color::White);
+        let black = TimeManager::new(&limits, Color::Black);
+        assert!(white.get_soft_limit().unwrap() < white.get_hard_limit().unwrap());
+        assert!(white.get_hard_limit().unwrap() < Duration::from_millis(60000 / 5));
+        assert!(black.get_hard_limit().unwrap() < white.get_hard_limit().unwrap());
+
+        // last move before time control may use almost all the time, but not more
+        let limits = SearchLimits::parse_uci(&["wtime", "1000", "movestogo", "1"]);
+        let tm = TimeManager::new(&limits, Color::White);
+        assert_eq!(
+            tm.get_hard_limit(),
+            Some(Duration::from_millis(1000 - MOVE_OVERHEAD_MS))
+        );
+
+        let tm = TimeManager::new(&SearchLimits::from_movetime(500), Color::Black);
+        assert_eq!(tm.get_soft_limit(), tm.get_hard_limit());
+        let tm = TimeManager::new(&SearchLimits::parse_uci(&["infinite"]), Color::White);
+        assert_eq!(tm.get_deadline(), None);
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        let mut evaluator = Evaluator::new();
+
+        let mut depths = vec![];
+        let mv = evaluator.search(&board, &SearchLimits::from_depth(3), |info| {
+            depths.push(info.depth)
+        });
+        assert!(mv.is_some());
+        assert_eq!(depths, vec![1, 2, 3]);
+
+        // node limit is checked only now and then, but search must stop near it
+        let mut nodes = 0;
+        let limits = SearchLimits {
+            nodes: Some(5000),
+            ..Default::default()
+        };
+        evaluator.search(&board, &limits, |info| nodes = info.nodes);
+        assert!(nodes > 0 && nodes < 5000 + 1024);
+
+        // aborted iteration still gives the move of the previous one
+        let start = Instant::now();
+        let mv = evaluator.search(&board, &SearchLimits::from_movetime(200), |_| {});
+        assert!(mv.is_some());
+        assert!(start.elapsed() < Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        let mut full = vec![];
+        Evaluator::new().search(&board, &SearchLimits::from_depth(6), |info| {
+            full.push(info.nodes)
+        });
+        assert_eq!(full.len(), 6);
+
+        // search stops after the iteration that reaches the limit
+        let mut nodes = vec![];
+        let limits = SearchLimits {
+            nodes: Some(full[3]),
+            ..Default::default()
+        };
+        Evaluator::new().search(&board, &limits, |info| nodes.push(info.nodes));
+        assert_eq!(nodes, full[..4]);
+
+        // iteration cut by the limit is not reported, earlier ones stay under it
+        let limit = (full[4] + full[5]) / 2;
+        let mut nodes = vec![];
+        let limits = SearchLimits {
+            nodes: Some(limit),
+            ..Default::default()
+        };
+        Evaluator::new().search(&board, &limits, |info| nodes.push(info.nodes));
+        assert_eq!(nodes, full[..5]);
+        assert!(*nodes.last().unwrap() <= limit);
+    }
+}
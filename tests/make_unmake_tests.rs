@@ -0,0 +1,85 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use ::rust_chess::game::board::*;
+
+    // every legal move made in place must give the same board as the copying version,
+    // and unmaking it must restore the board exactly
+    fn check_all_moves(board: &ChessBoardState) {
+        let mut cur = *board;
+        for mv in board.get_all_moves_checked() {
+            let (expected, expected_res) = board.get_new_pos_after_move_for_eval(mv);
+            let undo = cur.make_move(mv);
+            assert_eq!(cur, expected, "{} {}", board.to_fen(), mv.get_uci_string());
+            assert_eq!(undo.result, expected_res);
+            cur.unmake_move(&undo);
+            assert_eq!(cur, *board, "{} {}", board.to_fen(), mv.get_uci_string());
+        }
+    }
+
+    #[test]
+    fn test_make_unmake_special_moves() {
+        let fens = [
+            // castling both sides for both colors
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+            // en passant
+            "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1",
+            "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1",
+            // promotions with and without capture
+            "1n2k3/P7/8/8/8/8/7p/4K1N1 w - - 0 1",
+            "1n2k3/P7/8/8/8/8/7p/4K1N1 b - - 0 1",
+        ];
+        for fen in fens {
+            check_all_moves(&ChessBoardState::from_fen(fen).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_make_unmake_random_games() {
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ];
+        // the game is also played in place on one board
+        let mut board = ChessBoardState::from_fen(fens[0]).unwrap();
+        let mut undos = vec![];
+        let mut positions = vec![];
+        random_positions(&fens, 54321, 10, 80, |cur, mv| {
+            check_all_moves(cur);
+            if undos.is_empty() {
+                board = *cur;
+            }
+            assert_eq!(board, *cur);
+            match mv {
+                Some(mv) => {
+                    positions.push(board);
+                    undos.push(board.make_move(mv));
+                }
+                // whole game taken back on the same board
+                None => {
+                    while let Some(undo) = undos.pop() {
+                        board.unmake_move(&undo);
+                        assert_eq!(board, positions.pop().unwrap());
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_null_move() {
+        let board = ChessBoardState::from_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 4 30").unwrap();
+        let mut cur = board;
+        let undo = cur.make_null_move();
+        assert_eq!(cur.turn, Color::Black);
+        assert_eq!(cur.en_passant, 0xFF);
+        assert_eq!(cur.hash, cur.compute_hash());
+        assert_eq!(cur.board, board.board);
+        cur.unmake_null_move(&undo);
+        assert_eq!(cur, board);
+    }
+}
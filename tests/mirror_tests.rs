@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+
+    const FENS: [&str; 8] = [
+        START_POS_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1",
+        "4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1",
+    ];
+
+    #[test]
+    fn test_mirror_position() {
+        let board = ChessBoardState::from_fen("4k2r/8/8/3Pp3/8/8/8/R3K3 w Qk e6 3 20").unwrap();
+        assert_eq!(
+            board.mirror().to_fen(),
+            "r3k3/8/8/8/3pP3/8/8/4K2R b Kq e3 3 20"
+        );
+        for fen in FENS {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let mirrored = board.mirror();
+            assert_eq!(mirrored.hash, mirrored.compute_hash());
+            assert_eq!(mirrored.mirror().to_fen(), board.to_fen());
+            assert_eq!(mirrored.mirror().hash, board.hash);
+            // mirrored position must also be valid by FEN rules
+            assert!(
+                ChessBoardState::from_fen(&mirrored.to_fen()).is_some(),
+                "{}",
+                fen
+            );
+
+            let mut moves: Vec<String> = board
+                .get_all_moves_checked()
+                .iter()
+                .map(|x| x.get_mirrored().get_uci_string())
+                .collect();
+            let mut expected: Vec<String> = mirrored
+                .get_all_moves_checked()
+                .iter()
+                .map(|x| x.get_uci_string())
+                .collect();
+            moves.sort();
+            expected.sort();
+            assert_eq!(moves, expected, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_evaluator_symmetry() {
+        for fen in FENS {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let mirrored = board.mirror();
+            let evaluator = Evaluator::new();
+            // static evaluation is from the point of view of white
+            assert_eq!(
+                evaluator.get_static_eval(&board),
+                -evaluator.get_static_eval(&mirrored),
+                "{}",
+                fen
+            );
+            for depth in [1, 3] {
+                // fresh tables, so both searches see exactly the same state
+                let res = Evaluator::new().evaluate(&board, depth);
+                let mirrored_res = Evaluator::new().evaluate(&mirrored, depth);
+                // search scores are from the point of view of side to move
+                assert_eq!(res.score, mirrored_res.score, "{} {}", fen, depth);
+                assert_eq!(
+                    res.best_move.map(|x| x.get_mirrored()),
+                    mirrored_res.best_move,
+                    "{} {}",
+                    fen,
+                    depth
+                );
+            }
+        }
+    }
+}
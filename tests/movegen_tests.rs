@@ -0,0 +1,87 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::rules::*;
+
+    fn get_sorted_uci<T: Copy + Into<ChessMove>>(moves: &[T]) -> Vec<String> {
+        let mut res: Vec<String> = moves.iter().map(|x| (*x).into().get_uci_string()).collect();
+        res.sort();
+        res
+    }
+
+    // moves filtered by making every pseudo legal one, the way it was done before
+    fn get_filtered_moves(board: &ChessBoardState) -> Vec<ChessMove> {
+        let mut res = board.get_all_moves();
+        res.retain(|mv| !board.is_move_allowed_by_rules(*mv));
+        res
+    }
+
+    #[test]
+    fn test_legal_moves_random_games() {
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ];
+        random_positions(&fens, 777, 20, 100, |board, _| {
+            let expected = get_sorted_uci(&get_filtered_moves(board));
+            assert_eq!(
+                get_sorted_uci(&board.get_legal_moves()),
+                expected,
+                "{}",
+                board.to_fen()
+            );
+
+            // captures and quiets split all moves without overlap
+            let mut split = board.get_legal_captures().to_vec();
+            split.extend_from_slice(&board.get_legal_quiets());
+            assert_eq!(get_sorted_uci(&split), expected, "{}", board.to_fen());
+            for mv in board.get_legal_captures().iter() {
+                let res = board.get_new_pos_after_move_for_eval(mv.unpack()).1;
+                assert!(res.remove != ChessPiece::None || res.new != ChessPiece::None);
+                assert_eq!(mv.is_capture(), res.remove != ChessPiece::None);
+            }
+        });
+    }
+
+    #[test]
+    fn test_pins_and_checks() {
+        let cases = [
+            // en passant would uncover the king along the rank
+            ("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", "b5c6", false),
+            // en passant removes the checking pawn
+            ("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", "e4d3", true),
+            // pinned knight can not move at all
+            ("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1", "e2c3", false),
+            // pinned rook moves along the pin and captures the pinner
+            ("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1", "e2e8", true),
+            ("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1", "e2d2", false),
+            // capture and block answer a single check
+            ("6k1/8/8/8/8/5n2/6B1/4K3 w - - 0 1", "g2f3", true),
+            ("4r1k1/8/8/8/R7/8/8/4K3 w - - 0 1", "a4e4", true),
+            // double check, only king moves
+            ("4r1k1/8/8/8/R7/5n2/6B1/4K3 w - - 0 1", "g2f3", false),
+            ("4r1k1/8/8/8/R7/5n2/6B1/4K3 w - - 0 1", "a4e4", false),
+            ("4r1k1/8/8/8/R7/5n2/6B1/4K3 w - - 0 1", "e1f1", true),
+            // king can not step back along the checking ray
+            ("4r1k1/8/8/8/8/8/4K3/8 w - - 0 1", "e2e1", false),
+            // castling into check
+            ("6k1/8/8/8/8/8/6r1/4K2R w K - 0 1", "e1g1", false),
+        ];
+        for (fen, mv_str, legal) in cases {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let moves = get_sorted_uci(&board.get_legal_moves());
+            assert_eq!(
+                moves.contains(&mv_str.to_string()),
+                legal,
+                "{} {}",
+                fen,
+                mv_str
+            );
+        }
+    }
+}
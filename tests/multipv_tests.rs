@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::limits::*;
+
+    #[test]
+    fn test_lines_ranked() {
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 b - - 0 10",
+        ];
+        for fen in fens {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let mut evaluator = Evaluator::new();
+            evaluator.set_multipv(3);
+            let mut last = None;
+            evaluator.search(&board, &SearchLimits::from_depth(4), |x| {
+                last = Some(x.clone())
+            });
+            let last = last.unwrap();
+            assert_eq!(last.lines.len(), 3, "{}", fen);
+            assert_eq!(Some(last.lines[0].mv), last.best_move, "{}", fen);
+            assert_eq!(last.lines[0].score, last.score, "{}", fen);
+            assert_eq!(last.lines[0].pv, last.pv, "{}", fen);
+            for (i, line) in last.lines.iter().enumerate() {
+                assert_eq!(
+                    line.pv.first().map(|x| x.unpack()),
+                    Some(line.mv),
+                    "{}",
+                    fen
+                );
+                for other in &last.lines[i + 1..] {
+                    assert_ne!(line.mv, other.mv, "{}", fen);
+                    assert!(line.score >= other.score, "{}", fen);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fewer_legal_moves() {
+        // only the king moves, two squares are free
+        let board = ChessBoardState::from_fen("7k/8/8/5Q2/8/8/8/K7 b - - 0 1").unwrap();
+        let legal = board.get_legal_moves().len();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_multipv(5);
+        let res = evaluator.evaluate(&board, 3);
+        assert!(legal < 5);
+        assert_eq!(res.lines.len(), legal);
+    }
+
+    #[test]
+    fn test_best_line_matches_single() {
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 b - - 0 10",
+        ];
+        for fen in fens {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let single = Evaluator::new().evaluate(&board, 4);
+            let mut evaluator = Evaluator::new();
+            evaluator.set_multipv(3);
+            let multi = evaluator.evaluate(&board, 4);
+            assert_eq!(single.lines.len(), 1, "{}", fen);
+            assert_eq!(single.best_move, Some(multi.lines[0].mv), "{}", fen);
+            assert_eq!(single.score, multi.lines[0].score, "{}", fen);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::packed_move::*;
+    use ::rust_chess::ordering::*;
+
+    fn get_ordered(
+        ordering: &MoveOrdering,
+        board: &ChessBoardState,
+        hash_move: Option<PackedMove>,
+        prev: PackedMove,
+    ) -> Vec<String> {
+        let moves = board.get_legal_moves();
+        let scores = ordering.score_moves(board, &moves, hash_move, 1, prev);
+        let mut res: Vec<(i32, String)> = moves
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (scores[i], x.get_uci_string()))
+            .collect();
+        res.sort_by_key(|x| -x.0);
+        res.into_iter().map(|x| x.1).collect()
+    }
+
+    fn pack(board: &ChessBoardState, uci: &str) -> PackedMove {
+        board.pack_move(board.get_chess_move_from_uci(uci).unwrap())
+    }
+
+    #[test]
+    fn test_move_order() {
+        // pawn takes the queen before rook takes the rook
+        let board = ChessBoardState::from_fen("3r2k1/8/8/2q5/1P1R4/8/7K/8 w - - 0 1").unwrap();
+        let mut ordering = MoveOrdering::new();
+        let order = get_ordered(&ordering, &board, None, PackedMove::NULL);
+        assert_eq!(order[..2], ["b4c5", "d4d8"]);
+
+        let hash_move = pack(&board, "h2h1");
+        let order = get_ordered(&ordering, &board, Some(hash_move), PackedMove::NULL);
+        assert_eq!(order[..3], ["h2h1", "b4c5", "d4d8"]);
+
+        // killers go right after captures, newest first
+        let prev = PackedMove::NULL;
+        ordering.update_cutoff(&board, pack(&board, "h2h1"), 1, 3, prev);
+        ordering.update_cutoff(&board, pack(&board, "h2h3"), 1, 3, prev);
+        let order = get_ordered(&ordering, &board, None, prev);
+        assert_eq!(order[..2], ["b4c5", "d4d8"]);
+        assert_eq!(order[2..4], ["h2h3", "h2h1"]);
+        assert_eq!(
+            ordering.get_killers(1),
+            [pack(&board, "h2h3"), pack(&board, "h2h1")]
+        );
+    }
+
+    #[test]
+    fn test_history_and_counter_moves() {
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        let mut ordering = MoveOrdering::new();
+        let prev = pack(&board, "e2e4");
+        let after = board.get_new_pos_after_move(prev.unpack());
+        let reply = pack(&after, "c7c5");
+        ordering.update_cutoff(&after, reply, 5, 4, prev);
+        assert_eq!(ordering.get_counter_move(prev), reply);
+        assert_eq!(ordering.get_history(Color::Black, reply), 16);
+        assert_eq!(ordering.get_history(Color::White, reply), 0);
+
+        // counter move is used after the same move, history everywhere
+        let order = get_ordered(&ordering, &after, None, prev);
+        assert_eq!(order[0], "c7c5");
+        let other = pack(&board, "d2d4");
+        let after = board.get_new_pos_after_move(other.unpack());
+        let order = get_ordered(&ordering, &after, None, other);
+        assert_eq!(order[0], "c7c5");
+
+        ordering.clear();
+        assert_eq!(ordering.get_counter_move(prev), PackedMove::NULL);
+        assert_eq!(ordering.get_history(Color::Black, reply), 0);
+    }
+
+    #[test]
+    fn test_cutoff_statistics() {
+        let board = ChessBoardState::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let mut evaluator = Evaluator::new();
+        assert_eq!(evaluator.get_first_move_cutoff_rate(), 0.0);
+        for depth in 1..=4 {
+            evaluator.evaluate(&board, depth);
+        }
+        assert!(evaluator.beta_cutoffs > 0);
+        assert!(evaluator.first_move_cutoffs <= evaluator.beta_cutoffs);
+        assert!(evaluator.get_first_move_cutoff_rate() > 0.5);
+    }
+}
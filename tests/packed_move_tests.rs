@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::packed_move::*;
+
+    #[test]
+    fn test_packed_move_round_trip() {
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "1n2k3/P7/8/8/8/8/7p/4K1N1 b - - 0 1",
+            "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1",
+        ];
+        for fen in fens {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            for mv in board.get_all_moves_checked() {
+                let packed = board.pack_move(mv);
+                assert_eq!(packed.unpack(), mv);
+                assert_eq!(PackedMove::from_code(packed.get_code()), packed);
+                assert!(!packed.is_null());
+                assert!(board.get_legal_moves().contains(&packed));
+            }
+        }
+    }
+
+    #[test]
+    fn test_packed_move_flags() {
+        let board = ChessBoardState::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let capture = board.pack_move(board.get_chess_move_from_uci("a7b8q").unwrap());
+        assert!(capture.is_capture() && capture.is_promotion());
+        let push = board.pack_move(board.get_chess_move_from_uci("a7a8n").unwrap());
+        assert!(!push.is_capture() && push.is_promotion());
+        assert_eq!(push.get_uci_string(), "a7a8n");
+
+        let board = ChessBoardState::from_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").unwrap();
+        let en_passant = board.pack_move(board.get_chess_move_from_uci("d5e6").unwrap());
+        assert!(en_passant.is_capture() && !en_passant.is_promotion());
+        assert_eq!(en_passant.get_from(), Pos::from_str("d5"));
+        assert_eq!(en_passant.get_to(), Pos::from_str("e6"));
+        assert!(PackedMove::NULL.is_null());
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::perft::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    const POSITION_4_MIRRORED: &str =
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+    const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+    const POSITION_6: &str =
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+    fn stats(
+        nodes: u64,
+        captures: u64,
+        en_passants: u64,
+        castles: u64,
+        promotions: u64,
+        checks: u64,
+        checkmates: u64,
+    ) -> PerftStats {
+        PerftStats {
+            nodes,
+            captures,
+            en_passants,
+            castles,
+            promotions,
+            checks,
+            checkmates,
+        }
+    }
+
+    fn perft_stats(fen: &str, depth: usize) -> PerftStats {
+        ChessBoardState::from_fen(fen).unwrap().perft_stats(depth)
+    }
+
+    #[test]
+    fn test_perft_start_position() {
+        assert_eq!(perft_stats(START_POS_FEN, 1), stats(20, 0, 0, 0, 0, 0, 0));
+        assert_eq!(perft_stats(START_POS_FEN, 2), stats(400, 0, 0, 0, 0, 0, 0));
+        assert_eq!(
+            perft_stats(START_POS_FEN, 3),
+            stats(8902, 34, 0, 0, 0, 12, 0)
+        );
+        assert_eq!(
+            perft_stats(START_POS_FEN, 4),
+            stats(197281, 1576, 0, 0, 0, 469, 8)
+        );
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        assert_eq!(perft_stats(KIWIPETE, 1), stats(48, 8, 0, 2, 0, 0, 0));
+        assert_eq!(perft_stats(KIWIPETE, 2), stats(2039, 351, 1, 91, 0, 3, 0));
+        assert_eq!(
+            perft_stats(KIWIPETE, 3),
+            stats(97862, 17102, 45, 3162, 0, 993, 1)
+        );
+    }
+
+    #[test]
+    fn test_perft_position_3() {
+        assert_eq!(perft_stats(POSITION_3, 1), stats(14, 1, 0, 0, 0, 2, 0));
+        assert_eq!(perft_stats(POSITION_3, 2), stats(191, 14, 0, 0, 0, 10, 0));
+        assert_eq!(
+            perft_stats(POSITION_3, 3),
+            stats(2812, 209, 2, 0, 0, 267, 0)
+        );
+        assert_eq!(
+            perft_stats(POSITION_3, 4),
+            stats(43238, 3348, 123, 0, 0, 1680, 17)
+        );
+    }
+
+    #[test]
+    fn test_perft_position_4() {
+        for fen in [POSITION_4, POSITION_4_MIRRORED] {
+            assert_eq!(perft_stats(fen, 1), stats(6, 0, 0, 0, 0, 0, 0));
+            assert_eq!(perft_stats(fen, 2), stats(264, 87, 0, 6, 48, 10, 0));
+            assert_eq!(perft_stats(fen, 3), stats(9467, 1021, 4, 0, 120, 38, 22));
+        }
+    }
+
+    #[test]
+    fn test_perft_positions_5_6() {
+        let board = ChessBoardState::from_fen(POSITION_5).unwrap();
+        assert_eq!(board.perft(1), 44);
+        assert_eq!(board.perft(2), 1486);
+        assert_eq!(board.perft(3), 62379);
+
+        let board = ChessBoardState::from_fen(POSITION_6).unwrap();
+        assert_eq!(board.perft(1), 46);
+        assert_eq!(board.perft(2), 2079);
+        assert_eq!(board.perft(3), 89890);
+    }
+
+    #[test]
+    fn test_divide() {
+        let board = ChessBoardState::from_fen(KIWIPETE).unwrap();
+        let divide = board.divide(2);
+        assert_eq!(divide.len(), 48);
+        assert_eq!(divide.iter().map(|(_, x)| x).sum::<u64>(), 2039);
+        let castle = divide.iter().find(|(mv, _)| mv.get_uci_string() == "e1c1");
+        assert_eq!(castle.unwrap().1, 43);
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::pgn::*;
+
+    const GAMES: &str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+
+[Event "Variations"]
+[SetUp "1"]
+[FEN "4k3/P7/8/8/8/8/8/4K3 w - - 0 40"]
+
+40. a8=Q+! $18 (40. a8N?! {weak} 40... Kd7 (40... Kf7) 41. Kd2) 40... Kd7 ; line comment
+41. Qd5+ *
+"#;
+
+    #[test]
+    fn test_read_games() {
+        let games = read_games(GAMES).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let game = &games[0];
+        assert_eq!(game.get_tag("White"), Some("Fischer, Robert J."));
+        assert_eq!(game.result, "1/2-1/2");
+        assert_eq!(game.moves.len(), 85);
+        assert_eq!(
+            game.moves[4].comment_after.as_deref(),
+            Some("This opening is called the Ruy Lopez.")
+        );
+        let last = game.moves.last().unwrap();
+        assert_eq!(
+            last.board.get_piece(Pos::from_str("e6")),
+            Some(ChessPiece::RookWhite)
+        );
+
+        let game = &games[1];
+        assert_eq!(game.result, "*");
+        assert_eq!(game.start.move_num, 40);
+        assert_eq!(game.moves.len(), 3);
+        assert_eq!(game.moves[0].nags, vec![1, 18]);
+        assert_eq!(game.moves[0].variations.len(), 1);
+        let variation = &game.moves[0].variations[0];
+        assert_eq!(variation.moves.len(), 3);
+        assert_eq!(variation.moves[0].nags, vec![6]);
+        assert_eq!(variation.moves[0].comment_after.as_deref(), Some("weak"));
+        assert_eq!(variation.moves[1].variations[0].moves.len(), 1);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let games = read_games(GAMES).unwrap();
+        let text = write_games(&games);
+        let read = read_games(&text).unwrap();
+        // first game already has the seven tag roster, second one gets it on export
+        assert_eq!(read[0], games[0]);
+        assert_eq!(read[1].moves, games[1].moves);
+        assert_eq!(read[1].get_tag("FEN"), games[1].get_tag("FEN"));
+        assert_eq!(read[1].get_tag("Event"), Some("Variations"));
+        assert_eq!(read[1].get_tag("Site"), Some("?"));
+        for line in text.lines() {
+            assert!(line.len() < 80);
+        }
+        let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        assert!(words.contains(
+            "40. a8=Q+ $1 $18 ( 40. a8=N $6 {weak} 40... Kd7 ( 40... Kf7 ) 41. Kd2 ) 40... Kd7"
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_comments() {
+        // comment of an empty variation stays with it
+        let game = PgnGame::from_pgn("1. e4 ( {no move} ) 1... e5 *").unwrap();
+        let variation = &game.moves[0].variations[0];
+        assert!(variation.moves.is_empty());
+        assert_eq!(variation.comment.as_deref(), Some("no move"));
+        let read = PgnGame::from_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(read.moves, game.moves);
+
+        // '}' would end the comment early and the rest would be read as moves
+        let mut game = PgnGame::new(ChessBoardState::from_fen(START_POS_FEN).unwrap());
+        for san in ["e4", "e5"] {
+            let mv = game.get_final_board().get_chess_move_from_san(san).unwrap();
+            game.push_move(mv);
+        }
+        game.moves[0].comment_after = Some("threat {Qh5} next".to_string());
+        let read = PgnGame::from_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(read.moves.len(), 2);
+        assert_eq!(
+            read.moves[0].comment_after.as_deref(),
+            Some("threat {Qh5 next")
+        );
+    }
+
+    #[test]
+    fn test_write_new_game() {
+        let mut game = PgnGame::new(ChessBoardState::from_fen(START_POS_FEN).unwrap());
+        game.set_tag("White", "Me");
+        for san in ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+            let mv = game.get_final_board().get_chess_move_from_san(san).unwrap();
+            game.push_move(mv);
+        }
+        game.result = "1-0".to_string();
+        assert_eq!(
+            game.to_pgn(),
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Me\"]\n\
+             [Black \"?\"]\n[Result \"1-0\"]\n\n1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = read_games("[Event \"x\"]\n\n1. e4 e5 2. Ke3 *").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 13));
+
+        let err = read_games("1. e4 (1. d4 e5").unwrap_err();
+        assert_eq!(err.message, "Unterminated variation");
+
+        let err = read_games("1. e4 {never closed").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 7));
+
+        let err = read_games("[Event \"x]\n1. e4 *").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+
+        assert!(read_games("1. e4 e5 )").is_err());
+    }
+}
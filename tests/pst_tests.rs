@@ -0,0 +1,67 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+
+    #[test]
+    fn test_static_eval_symmetry() {
+        let evaluator = Evaluator::new();
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        assert_eq!(evaluator.get_static_eval(&board), 0);
+        assert_eq!(evaluator.get_eval_terms(&board).phase, 24);
+
+        // same positions with colors and ranks swapped
+        let pairs = [
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1",
+            ),
+            (
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+                "8/4p1p1/8/1r3P1K/kp5R/3P4/2P5/8 b - - 0 1",
+            ),
+            (
+                "4k3/6P1/8/8/8/8/1p6/4K3 w - - 0 1",
+                "4k3/1P6/8/8/8/8/6p1/4K3 b - - 0 1",
+            ),
+        ];
+        for (fen, mirrored) in pairs {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let mirrored = ChessBoardState::from_fen(mirrored).unwrap();
+            assert_eq!(
+                evaluator.get_static_eval(&board),
+                -evaluator.get_static_eval(&mirrored),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_incremental_terms() {
+        let evaluator = Evaluator::new();
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1",
+        ];
+        // terms carried along the game, None before its first move
+        let mut terms = None;
+        random_positions(&fens, 4242, 10, 80, |board, mv| {
+            let Some(mv) = mv else {
+                terms = None;
+                return;
+            };
+            let before = terms.unwrap_or_else(|| evaluator.get_eval_terms(board));
+            let mut after = *board;
+            let undo = after.make_move(mv);
+            let cur = Evaluator::get_terms_after_move(before, &after, &undo);
+            assert_eq!(cur, evaluator.get_eval_terms(&after), "{}", after.to_fen());
+            terms = Some(cur);
+        });
+    }
+}
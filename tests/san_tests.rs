@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::game::board::*;
+
+    fn san_of(fen: &str, mv: &str) -> String {
+        let board = ChessBoardState::from_fen(fen).unwrap();
+        let mv = board.get_chess_move_from_uci(mv).unwrap();
+        board.get_san_string(mv)
+    }
+
+    #[test]
+    fn test_san_generation() {
+        assert_eq!(san_of(START_POS_FEN, "g1f3"), "Nf3");
+        assert_eq!(san_of(START_POS_FEN, "e2e4"), "e4");
+        assert_eq!(
+            san_of(
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                "e4d5"
+            ),
+            "exd5"
+        );
+        assert_eq!(
+            san_of("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1"),
+            "O-O"
+        );
+        assert_eq!(
+            san_of("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8"),
+            "O-O-O"
+        );
+        assert_eq!(san_of("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), "a8=Q+");
+        assert_eq!(san_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), "exd6");
+    }
+
+    #[test]
+    fn test_san_disambiguation_and_mate() {
+        // both rooks can reach d1
+        assert_eq!(
+            san_of("6k1/5ppp/8/8/8/8/8/R2q1RK1 w - - 0 1", "a1d1"),
+            "Raxd1"
+        );
+        // knights on the same file
+        assert_eq!(san_of("4k3/8/8/8/1N6/8/1N6/4K3 w - - 0 1", "b4d3"), "N4d3");
+        // three queens need both file and rank
+        assert_eq!(san_of("2k5/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "h4e1"), "Qh4e1");
+        assert_eq!(san_of("2k5/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "e4e1"), "Qee1");
+        assert_eq!(san_of("2k5/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "h1e1"), "Q1e1");
+        assert_eq!(san_of("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+    }
+
+    #[test]
+    fn test_san_parsing() {
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        for (san, uci) in [
+            ("Nf3", "g1f3"),
+            ("e4", "e2e4"),
+            ("Ng1-f3", "g1f3"),
+            ("e2e4", "e2e4"),
+        ] {
+            assert_eq!(
+                board.get_chess_move_from_san(san).unwrap().get_uci_string(),
+                uci
+            );
+        }
+        assert!(board.get_chess_move_from_san("Ne4").is_none());
+        assert!(board.get_chess_move_from_san("O-O").is_none());
+
+        let board = ChessBoardState::from_fen(
+            "r3k2r/p1pp1pb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        assert_eq!(
+            board
+                .get_chess_move_from_san("0-0")
+                .unwrap()
+                .get_uci_string(),
+            "e1g1"
+        );
+        assert_eq!(
+            board
+                .get_chess_move_from_san("O-O-O")
+                .unwrap()
+                .get_uci_string(),
+            "e1c1"
+        );
+        assert_eq!(
+            board
+                .get_chess_move_from_san("Qxf6")
+                .unwrap()
+                .get_uci_string(),
+            "f3f6"
+        );
+        assert_eq!(
+            board
+                .get_chess_move_from_san("Ng4")
+                .unwrap()
+                .get_uci_string(),
+            "e5g4"
+        );
+
+        let board = ChessBoardState::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board
+                .get_chess_move_from_san("b8=Q")
+                .unwrap()
+                .get_uci_string(),
+            "b7b8q"
+        );
+        assert_eq!(
+            board
+                .get_chess_move_from_san("b8Q")
+                .unwrap()
+                .get_uci_string(),
+            "b7b8q"
+        );
+        assert_eq!(
+            board
+                .get_chess_move_from_san("bxa8=N+")
+                .unwrap()
+                .get_uci_string(),
+            "b7a8n"
+        );
+        assert!(board.get_chess_move_from_san("b8").is_none());
+
+        let board = ChessBoardState::from_fen("r6r/4k3/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(board.get_chess_move_from_san("Rd8").is_none());
+        assert_eq!(
+            board
+                .get_chess_move_from_san("Rad8")
+                .unwrap()
+                .get_uci_string(),
+            "a8d8"
+        );
+    }
+
+    #[test]
+    fn test_san_round_trip() {
+        let board = ChessBoardState::from_fen(
+            "r3k2r/p1pp1pb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        for mv in board.get_all_moves_checked() {
+            let san = board.get_san_string(mv);
+            assert_eq!(board.get_chess_move_from_san(&san), Some(mv), "{}", san);
+        }
+    }
+}
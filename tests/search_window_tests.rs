@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::limits::*;
+
+    #[test]
+    fn test_aspiration_matches_full_window() {
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        for fen in fens {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let mut evaluator = Evaluator::new();
+            let mut last = None;
+            evaluator.search(&board, &SearchLimits::from_depth(5), |x| {
+                last = Some(x.clone())
+            });
+            let last = last.unwrap();
+            // same tables, so searching again without a window must agree
+            let full = evaluator.evaluate(&board, 5);
+            assert_eq!(last.score, full.score, "{}", fen);
+            assert_eq!(last.best_move, full.best_move, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_window_widens_on_fail_high() {
+        // score jumps from material to mate between iterations
+        let board = ChessBoardState::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let mut evaluator = Evaluator::new();
+        let mut results = vec![];
+        evaluator.search(&board, &SearchLimits::from_depth(6), |x| {
+            results.push(x.clone())
+        });
+        assert!(!Score::is_mate(results[0].score));
+        let last = results.last().unwrap();
+        assert_eq!(last.get_score(), Score::Mate(2));
+        for res in results {
+            assert_eq!(res.best_move, res.pv.first().map(|x| x.unpack()));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+
+    #[test]
+    fn test_see_values() {
+        let cases = [
+            // undefended pawn
+            ("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5", 100),
+            // queen takes a pawn defended by a pawn
+            ("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", "d1d5", -800),
+            // second rook behind the first one joins the exchange
+            ("4k3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5", 100),
+            ("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5", -400),
+            // queen behind the bishop takes back the recapturing pawn
+            ("4k3/6p1/5n2/8/3B4/2Q5/8/4K3 w - - 0 1", "d4f6", 100),
+            ("4k3/6p1/5n2/8/3B4/8/8/4K3 w - - 0 1", "d4f6", 0),
+            // king recaptures only when the square is no longer defended
+            ("4k3/8/8/8/8/2b5/3p4/3RK3 w - - 0 1", "d1d2", -100),
+            ("4k3/8/8/8/1b6/2b5/3p4/3RK3 w - - 0 1", "d1d2", -400),
+            ("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1", "d5e6", 100),
+            // promotions gain the difference, unless the new piece is lost
+            ("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q", 800),
+            ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q", -100),
+            ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q", 1300),
+        ];
+        for (fen, mv_str, expected) in cases {
+            let board = ChessBoardState::from_fen(fen).unwrap();
+            let mv = board.get_chess_move_from_uci(mv_str).unwrap();
+            assert_eq!(board.see(mv), expected, "{} {}", fen, mv_str);
+        }
+    }
+
+    #[test]
+    fn test_quiescence_skips_losing_captures() {
+        // rook takes a defended pawn only if it loses the rook, best is to keep material
+        let board = ChessBoardState::from_fen("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        let mut evaluator = Evaluator::new();
+        let res = evaluator.evaluate(&board, 1);
+        assert_ne!(res.best_move.unwrap().get_uci_string(), "d2d5");
+        assert!(res.score > -200);
+    }
+}
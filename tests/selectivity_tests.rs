@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+
+    fn get_evaluator(null_move: bool, lmr: bool, extensions: bool) -> Evaluator {
+        let mut res = Evaluator::new();
+        res.set_null_move_pruning(null_move);
+        res.set_late_move_reductions(lmr);
+        res.set_check_extensions(extensions);
+        res
+    }
+
+    #[test]
+    fn test_all_combinations_find_mate() {
+        let board = ChessBoardState::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        for i in 0..8 {
+            let mut evaluator = get_evaluator(i & 1 != 0, i & 2 != 0, i & 4 != 0);
+            let res = evaluator.evaluate(&board, 5);
+            assert_eq!(res.get_score(), Score::Mate(2), "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_selectivity_saves_nodes() {
+        let board = ChessBoardState::from_fen(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        )
+        .unwrap();
+        let full = get_evaluator(false, false, false).evaluate(&board, 4);
+        for (null_move, lmr) in [(true, false), (false, true)] {
+            let res = get_evaluator(null_move, lmr, false).evaluate(&board, 4);
+            assert!(res.nodes < full.nodes, "{} {}", null_move, lmr);
+        }
+    }
+
+    #[test]
+    fn test_no_null_move_with_pawns_only() {
+        // zugzwang is common with only kings and pawns, so null move is never tried there
+        let board = ChessBoardState::from_fen("8/8/8/3k4/8/3K4/3P4/8 w - - 0 1").unwrap();
+        for depth in [4, 6] {
+            let with_null = get_evaluator(true, false, false).evaluate(&board, depth);
+            let without = get_evaluator(false, false, false).evaluate(&board, depth);
+            assert_eq!(with_null.nodes, without.nodes);
+            assert_eq!(with_null.score, without.score);
+            assert_eq!(with_null.pv, without.pv);
+        }
+    }
+}
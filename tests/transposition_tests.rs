@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use ::rust_chess::evaluation::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::transposition::*;
+
+    fn entry(key: u64, depth: u8, score: i32) -> TTEntry {
+        TTEntry {
+            key,
+            depth,
+            score,
+            bound: Bound::Exact,
+            best_move: None,
+        }
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let mut tt = TranspositionTable::new(1);
+        assert!(tt.len().is_power_of_two());
+        assert!(tt.len() * std::mem::size_of::<Option<TTEntry>>() <= 1024 * 1024);
+        assert_eq!(tt.probe(42), None);
+
+        tt.store(entry(42, 3, 150));
+        assert_eq!(tt.probe(42), Some(entry(42, 3, 150)));
+        // same slot, different key
+        assert_eq!(tt.probe(42 + tt.len() as u64), None);
+
+        // shallower result does not replace deeper one of the same position
+        tt.store(entry(42, 2, 50));
+        assert_eq!(tt.probe(42).unwrap().depth, 3);
+        tt.store(entry(42, 4, 50));
+        assert_eq!(tt.probe(42).unwrap().depth, 4);
+
+        // other position always replaces
+        let other = 42 + tt.len() as u64;
+        tt.store(entry(other, 1, 0));
+        assert_eq!(tt.probe(42), None);
+        assert_eq!(tt.probe(other), Some(entry(other, 1, 0)));
+
+        tt.clear();
+        assert_eq!(tt.probe(other), None);
+        assert_eq!(tt.get_hashfull(), 0);
+    }
+
+    #[test]
+    fn test_search_uses_table() {
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.evaluate(&board, 4);
+        assert!(evaluator.tt_probes > 0);
+        // next iteration from the same root finds previous results
+        evaluator.evaluate(&board, 5);
+        assert!(evaluator.tt_hits > 0);
+        assert!(evaluator.get_tt_hit_rate() > 0.0 && evaluator.get_tt_hit_rate() <= 1.0);
+
+        // finding the mate must not depend on what is in the table
+        let board = ChessBoardState::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        evaluator.set_hash_size(1);
+        for depth in [2, 4] {
+            let res = evaluator.evaluate(&board, depth);
+            assert_eq!(res.best_move.unwrap().get_uci_string(), "a1a8");
+            assert_eq!(res.get_score(), Score::Mate(1));
+        }
+    }
+}
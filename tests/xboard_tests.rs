@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // runs the engine binary on the commands and returns the lines it printed
+    fn run_engine(commands: &[&str]) -> Vec<String> {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rust_chess"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut input = child.stdin.take().unwrap();
+        for command in commands {
+            writeln!(input, "{}", command).unwrap();
+        }
+        drop(input);
+        let output = child.wait_with_output().unwrap();
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_no_move_after_fifty_move_draw() {
+        // ping waits for the search, so every move is printed before pong
+        let output = run_engine(&[
+            "xboard",
+            "protover 2",
+            "sd 3",
+            "setboard 7k/8/8/8/8/8/R7/K7 w - - 99 80",
+            "usermove a2a3",
+            "ping 1",
+            "go",
+            "ping 2",
+        ]);
+        assert!(output.contains(&"1/2-1/2 {Fifty move rule}".to_string()));
+        assert!(output.contains(&"pong 1".to_string()));
+        assert!(output.contains(&"pong 2".to_string()));
+        assert!(
+            !output.iter().any(|x| x.starts_with("move ")),
+            "{:?}",
+            output
+        );
+
+        // engine still answers a move in a game that goes on
+        let output = run_engine(&[
+            "xboard",
+            "protover 2",
+            "sd 3",
+            "setboard 7k/8/8/8/8/8/R7/K7 w - - 98 80",
+            "usermove a2a3",
+            "ping 1",
+        ]);
+        assert!(
+            output.iter().any(|x| x.starts_with("move ")),
+            "{:?}",
+            output
+        );
+    }
+}
@@ -0,0 +1,82 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::*;
+    use ::rust_chess::game::board::*;
+    use ::rust_chess::game::history::*;
+
+    fn play(board: &ChessBoardState, moves: &[&str]) -> ChessBoardState {
+        let mut res = *board;
+        for mv_str in moves {
+            let mv = res.get_chess_move_from_uci(mv_str).unwrap();
+            res = res.get_new_pos_after_move(mv);
+        }
+        res
+    }
+
+    #[test]
+    fn test_incremental_hash_random_games() {
+        let fens = [
+            START_POS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ];
+        random_positions(&fens, 12345, 20, 100, |board, _| {
+            assert_eq!(board.hash, board.compute_hash(), "{}", board.to_fen());
+        });
+    }
+
+    #[test]
+    fn test_hash_identifies_position() {
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        let a = play(&board, &["g1f3", "g8f6", "b1c3"]);
+        let b = play(&board, &["b1c3", "g8f6", "g1f3"]);
+        assert_eq!(a.hash, b.hash);
+        assert_ne!(a.hash, board.hash);
+
+        // same pieces but different side to move, castling or en passant
+        let c = ChessBoardState::from_fen("4k3/8/8/8/4p3/8/3P4/R3K3 w Q - 0 1").unwrap();
+        let d = ChessBoardState::from_fen("4k3/8/8/8/4p3/8/3P4/R3K3 b Q - 0 1").unwrap();
+        let e = ChessBoardState::from_fen("4k3/8/8/8/4p3/8/3P4/R3K3 w - - 0 1").unwrap();
+        assert_ne!(c.hash, d.hash);
+        assert_ne!(c.hash, e.hash);
+        let ep = play(&c, &["d2d4"]);
+        let no_ep = ChessBoardState::from_fen("4k3/8/8/8/3Pp3/8/8/R3K3 b Q - 0 1").unwrap();
+        assert_eq!(
+            ep.hash,
+            ChessBoardState::from_fen(&ep.to_fen()).unwrap().hash
+        );
+        assert_ne!(ep.hash, no_ep.hash);
+        assert_eq!(play(&ep, &["e8d8"]).hash, play(&no_ep, &["e8d8"]).hash);
+    }
+
+    #[test]
+    fn test_en_passant_only_when_capturable() {
+        // nothing can take on e3
+        let board = ChessBoardState::from_fen(START_POS_FEN).unwrap();
+        let no_ep =
+            ChessBoardState::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+                .unwrap();
+        assert_eq!(play(&board, &["e2e4"]), no_ep);
+
+        // pawn that could take is pinned along the rank, square given in fen is dropped too
+        let board = ChessBoardState::from_fen("8/8/8/8/k2p3R/8/4P3/4K3 w - - 0 1").unwrap();
+        let no_ep = ChessBoardState::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(play(&board, &["e2e4"]), no_ep);
+        let ep = ChessBoardState::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - e3 0 1").unwrap();
+        assert_eq!(ep, no_ep);
+
+        // position after the double push repeats twice more
+        let mut game = Game::from_fen(START_POS_FEN).unwrap();
+        let moves = [
+            "e2e4", "g8f6", "g1f3", "f6g8", "f3g1", "g8f6", "g1f3", "f6g8", "f3g1",
+        ];
+        for mv_str in moves {
+            let mv = game.get_board().get_chess_move_from_uci(mv_str).unwrap();
+            assert!(game.push(mv));
+        }
+        assert!(game.is_threefold_repetition());
+    }
+}